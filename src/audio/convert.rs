@@ -0,0 +1,44 @@
+/*
+ * Numeric conversions shared by the sample math.
+ * Sample counts and rates stay far below 2^24, where an f32 holds every integer exactly,
+ * so the float conversions only round values that are fractional to begin with.
+ */
+use std::time::Duration;
+
+#[allow(clippy::cast_possible_truncation)]
+pub fn to_f32(value: impl Into<f64>) -> f32 {
+    value.into() as f32
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub const fn count_to_f32(count: usize) -> f32 {
+    count as f32
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub fn to_i16(sample: f32) -> i16 {
+    (sample * 32767.0).clamp(-32768.0, 32767.0) as i16
+}
+
+pub fn sample_count(duration: Duration, sample_rate: u32) -> usize {
+    usize::try_from(duration.as_micros() * u128::from(sample_rate) / 1_000_000)
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_count() {
+        assert_eq!(sample_count(Duration::from_millis(150), 16000), 2400);
+        assert_eq!(sample_count(Duration::ZERO, 16000), 0);
+    }
+
+    #[test]
+    fn test_to_i16_clamps() {
+        assert_eq!(to_i16(0.5), 16383);
+        assert_eq!(to_i16(2.0), i16::MAX);
+        assert_eq!(to_i16(-2.0), i16::MIN);
+    }
+}
@@ -0,0 +1,192 @@
+use super::convert::{count_to_f32, to_f32};
+use std::f32::consts::PI;
+
+pub const FRAME_LENGTH: usize = 400;
+pub const HOP_LENGTH: usize = 160;
+const FFT_SIZE: usize = 512;
+const MEL_FILTERS: usize = 26;
+const COEFFICIENTS: usize = 13;
+const PRE_EMPHASIS: f32 = 0.97;
+
+pub struct Mfcc {
+    window: Vec<f32>,
+    filters: Vec<Vec<(usize, f32)>>,
+}
+
+impl Mfcc {
+    pub fn new(sample_rate: u32) -> Self {
+        let window = (0..FRAME_LENGTH)
+            .map(|i| {
+                0.46f32.mul_add(
+                    -(2.0 * PI * count_to_f32(i) / count_to_f32(FRAME_LENGTH - 1)).cos(),
+                    0.54,
+                )
+            })
+            .collect();
+        Self {
+            window,
+            filters: Self::mel_filters(sample_rate),
+        }
+    }
+
+    fn hz_to_mel(hz: f32) -> f32 {
+        2595.0 * (1.0 + hz / 700.0).log10()
+    }
+
+    fn mel_to_hz(mel: f32) -> f32 {
+        700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+    }
+
+    fn mel_filters(sample_rate: u32) -> Vec<Vec<(usize, f32)>> {
+        let max_mel = Self::hz_to_mel(to_f32(sample_rate) / 2.0);
+        let bins: Vec<f32> = (0..MEL_FILTERS + 2)
+            .map(|i| {
+                let hz = Self::mel_to_hz(max_mel * count_to_f32(i) / count_to_f32(MEL_FILTERS + 1));
+                hz * count_to_f32(FFT_SIZE) / to_f32(sample_rate)
+            })
+            .collect();
+
+        bins.windows(3)
+            .map(|edges| {
+                let (left, center, right) = (edges[0], edges[1], edges[2]);
+                (0..=FFT_SIZE / 2)
+                    .filter_map(|bin| {
+                        let position = count_to_f32(bin);
+                        let weight = if position <= center {
+                            (position - left) / (center - left).max(f32::EPSILON)
+                        } else {
+                            (right - position) / (right - center).max(f32::EPSILON)
+                        };
+                        (weight > 0.0).then_some((bin, weight))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn compute(&self, frame: &[f32]) -> Vec<f32> {
+        let mut real = vec![0.0; FFT_SIZE];
+        let mut imag = vec![0.0; FFT_SIZE];
+        let mut previous = 0.0;
+        for (i, (&sample, &weight)) in frame.iter().zip(&self.window).enumerate() {
+            real[i] = PRE_EMPHASIS.mul_add(-previous, sample) * weight;
+            previous = sample;
+        }
+        fft(&mut real, &mut imag);
+
+        let log_energies: Vec<f32> = self
+            .filters
+            .iter()
+            .map(|filter| {
+                let energy: f32 = filter
+                    .iter()
+                    .map(|&(bin, weight)| {
+                        real[bin].mul_add(real[bin], imag[bin] * imag[bin]) * weight
+                    })
+                    .sum();
+                energy.max(1e-10).ln()
+            })
+            .collect();
+
+        (1..COEFFICIENTS)
+            .map(|k| {
+                log_energies
+                    .iter()
+                    .enumerate()
+                    .map(|(n, &energy)| {
+                        energy
+                            * (PI * count_to_f32(k) * (count_to_f32(n) + 0.5)
+                                / count_to_f32(MEL_FILTERS))
+                            .cos()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    pub fn compute_all(&self, samples: &[f32]) -> Vec<Vec<f32>> {
+        if samples.len() < FRAME_LENGTH {
+            return Vec::new();
+        }
+        (0..=samples.len() - FRAME_LENGTH)
+            .step_by(HOP_LENGTH)
+            .map(|start| self.compute(&samples[start..start + FRAME_LENGTH]))
+            .collect()
+    }
+}
+
+fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let n = real.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= n {
+        let angle = -2.0 * PI / count_to_f32(length);
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * count_to_f32(k)).sin_cos();
+                let (a, b) = (start + k, start + k + length / 2);
+                let t_real = real[b].mul_add(cos, -(imag[b] * sin));
+                let t_imag = real[b].mul_add(sin, imag[b] * cos);
+                real[b] = real[a] - t_real;
+                imag[b] = imag[a] - t_imag;
+                real[a] += t_real;
+                imag[a] += t_imag;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft_finds_tone() {
+        let mut real: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| (2.0 * PI * 8.0 * count_to_f32(i) / count_to_f32(FFT_SIZE)).sin())
+            .collect();
+        let mut imag = vec![0.0; FFT_SIZE];
+        fft(&mut real, &mut imag);
+
+        let magnitudes: Vec<f32> = real
+            .iter()
+            .zip(&imag)
+            .take(FFT_SIZE / 2)
+            .map(|(r, i)| r.hypot(*i))
+            .collect();
+        let peak = magnitudes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(bin, _)| bin);
+        assert_eq!(peak, Some(8));
+    }
+
+    #[test]
+    fn test_mfcc_frames() {
+        let mfcc = Mfcc::new(16000);
+        let samples: Vec<f32> = (0..16000)
+            .map(|i| (2.0 * PI * 440.0 * to_f32(i) / 16000.0).sin() * 0.5)
+            .collect();
+
+        let frames = mfcc.compute_all(&samples);
+        assert_eq!(frames.len(), (16000 - FRAME_LENGTH) / HOP_LENGTH + 1);
+        assert!(frames
+            .iter()
+            .all(|frame| frame.len() == COEFFICIENTS - 1 && frame.iter().all(|c| c.is_finite())));
+    }
+}
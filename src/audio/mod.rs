@@ -0,0 +1,6 @@
+pub mod convert;
+pub mod mfcc;
+pub mod normalize;
+
+pub use convert::{count_to_f32, sample_count, to_f32, to_i16};
+pub use normalize::{downmix, encode_wav, normalize_wav, resample, Resampler, TARGET_SAMPLE_RATE};
@@ -0,0 +1,323 @@
+use super::convert::{count_to_f32, to_f32};
+use crate::error::{Error, Result};
+use bytes::Bytes;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use std::f64::consts::PI;
+use std::io::Cursor;
+
+pub const TARGET_SAMPLE_RATE: u32 = 16000;
+const LOW_PASS_TAPS: u16 = 63;
+
+/*
+ * Windowed-sinc FIR that removes everything above the target Nyquist frequency before decimating.
+ * The edges of a stream are extended with their first and last sample, so the output has no delay
+ * and keeps the length of the input.
+ */
+struct LowPass {
+    taps: Vec<f32>,
+    buffer: Vec<f32>,
+}
+
+impl LowPass {
+    fn new(cutoff: f64) -> Self {
+        let center = f64::from(LOW_PASS_TAPS - 1) / 2.0;
+        let window_length = f64::from(LOW_PASS_TAPS - 1);
+        let taps: Vec<f64> = (0..LOW_PASS_TAPS)
+            .map(f64::from)
+            .map(|k| {
+                let x = k - center;
+                let sinc = if x == 0.0 {
+                    2.0 * cutoff
+                } else {
+                    (2.0 * PI * cutoff * x).sin() / (PI * x)
+                };
+                let window = 0.08f64.mul_add(
+                    (4.0 * PI * k / window_length).cos(),
+                    0.5f64.mul_add(-(2.0 * PI * k / window_length).cos(), 0.42),
+                );
+                sinc * window
+            })
+            .collect();
+        let sum: f64 = taps.iter().sum();
+        Self {
+            taps: taps.iter().map(|tap| to_f32(tap / sum)).collect(),
+            buffer: Vec::new(),
+        }
+    }
+
+    const fn delay(&self) -> usize {
+        self.taps.len() / 2
+    }
+
+    fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.buffer.is_empty() {
+            if let Some(&first) = input.first() {
+                self.buffer.resize(self.delay(), first);
+            }
+        }
+        self.buffer.extend_from_slice(input);
+        if self.buffer.len() < self.taps.len() {
+            return Vec::new();
+        }
+
+        let output = self
+            .buffer
+            .windows(self.taps.len())
+            .map(|window| window.iter().zip(&self.taps).map(|(x, h)| x * h).sum())
+            .collect();
+        self.buffer.drain(..=self.buffer.len() - self.taps.len());
+        output
+    }
+
+    fn flush(&mut self) -> Vec<f32> {
+        let Some(&last) = self.buffer.last() else {
+            return Vec::new();
+        };
+        let output = self.process(&vec![last; self.delay()]);
+        self.buffer.clear();
+        output
+    }
+}
+
+pub struct Resampler {
+    from: u32,
+    to: u32,
+    // Position of the next output sample in the buffered input, in units of 1/`to` input samples.
+    position: u64,
+    previous: Option<f32>,
+    low_pass: Option<LowPass>,
+}
+
+impl Resampler {
+    pub fn new(from: u32, to: u32) -> Self {
+        Self {
+            from,
+            to,
+            position: 0,
+            previous: None,
+            low_pass: (from > to).then(|| LowPass::new(f64::from(to) / f64::from(from) / 2.0)),
+        }
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        match &mut self.low_pass {
+            Some(low_pass) => {
+                let filtered = low_pass.process(input);
+                self.interpolate(&filtered)
+            }
+            None => self.interpolate(input),
+        }
+    }
+
+    fn interpolate(&mut self, input: &[f32]) -> Vec<f32> {
+        let mut samples = Vec::with_capacity(input.len() + 1);
+        samples.extend(self.previous);
+        samples.extend_from_slice(input);
+
+        let (from, to) = (u64::from(self.from), u64::from(self.to));
+        let length = samples.len() as u64;
+        let mut output = Vec::with_capacity(usize::try_from(length * to / from).unwrap_or(0) + 1);
+        while self.position + to < length * to {
+            let Ok(index) = usize::try_from(self.position / to) else {
+                break;
+            };
+            let fraction = to_f32(u32::try_from(self.position % to).unwrap_or(0)) / to_f32(self.to);
+            let current = samples[index];
+            output.push((samples[index + 1] - current).mul_add(fraction, current));
+            self.position += from;
+        }
+
+        if let Some(&last) = samples.last() {
+            self.position -= (length - 1) * to;
+            self.previous = Some(last);
+        }
+        output
+    }
+
+    pub fn flush(&mut self) -> Vec<f32> {
+        let tail = self
+            .low_pass
+            .as_mut()
+            .map(LowPass::flush)
+            .unwrap_or_default();
+        let mut output = self.interpolate(&tail);
+        if let Some(last) = self.previous.take() {
+            while self.position < u64::from(self.to) {
+                output.push(last);
+                self.position += u64::from(self.from);
+            }
+        }
+        self.position = 0;
+        output
+    }
+}
+
+pub fn downmix(samples: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(usize::from(channels))
+        .map(|frame| frame.iter().sum::<f32>() / count_to_f32(frame.len()))
+        .collect()
+}
+
+pub fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to {
+        return samples.to_vec();
+    }
+    let mut resampler = Resampler::new(from, to);
+    let mut output = resampler.process(samples);
+    output.extend(resampler.flush());
+    output
+}
+
+pub fn normalize_wav(audio: &[u8]) -> Result<Vec<f32>> {
+    let reader = WavReader::new(Cursor::new(audio))?;
+    let spec = reader.spec();
+    if spec.channels == 0 || spec.sample_rate == 0 {
+        return Err(Error::AudioCodec(format!(
+            "Unsupported WAV format: {} channels at {} Hz",
+            spec.channels, spec.sample_rate
+        )));
+    }
+
+    let samples: Vec<f32> = match (spec.sample_format, spec.bits_per_sample) {
+        (SampleFormat::Float, 32) => reader
+            .into_samples::<f32>()
+            .collect::<std::result::Result<_, _>>()?,
+        (SampleFormat::Int, bits @ 8..=32) => {
+            let scale = 2f32.powi(i32::from(bits) - 1);
+            reader
+                .into_samples::<i32>()
+                .map(|sample| sample.map(|sample| to_f32(sample) / scale))
+                .collect::<std::result::Result<_, _>>()?
+        }
+        (format, bits) => {
+            return Err(Error::AudioCodec(format!(
+                "Unsupported WAV sample format: {bits} bit {format:?}"
+            )))
+        }
+    };
+
+    Ok(resample(
+        &downmix(&samples, spec.channels),
+        spec.sample_rate,
+        TARGET_SAMPLE_RATE,
+    ))
+}
+
+pub fn encode_wav(samples: &[f32]) -> Result<Bytes> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut cursor, spec)?;
+        for &sample in samples {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+    }
+
+    Ok(Bytes::from(cursor.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(spec: WavSpec, frames: i32, sample: impl Fn(i32) -> i32) -> Result<Vec<u8>> {
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = WavWriter::new(&mut cursor, spec)?;
+            for frame in 0..frames {
+                for _ in 0..spec.channels {
+                    writer.write_sample(sample(frame))?;
+                }
+            }
+            writer.finalize()?;
+        }
+        Ok(cursor.into_inner())
+    }
+
+    #[test]
+    fn test_resampler_matches_across_chunks() {
+        let input: Vec<f32> = (0..480).map(|i| (to_f32(i) / 20.0).sin()).collect();
+        let whole = resample(&input, 48000, 16000);
+
+        let mut resampler = Resampler::new(48000, 16000);
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(37) {
+            chunked.extend(resampler.process(chunk));
+        }
+        chunked.extend(resampler.flush());
+
+        assert_eq!(whole.len(), 160);
+        assert_eq!(chunked.len(), whole.len());
+        for (a, b) in whole.iter().zip(&chunked) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_resampler_attenuates_frequencies_above_nyquist() {
+        let tone = |frequency: f32| -> Vec<f32> {
+            (0..4800)
+                .map(|i| (2.0 * std::f32::consts::PI * frequency * to_f32(i) / 48000.0).sin())
+                .collect()
+        };
+        let rms = |samples: &[f32]| {
+            let inner = &samples[20..samples.len() - 20];
+            (inner.iter().map(|s| s * s).sum::<f32>() / count_to_f32(inner.len())).sqrt()
+        };
+
+        let passed = resample(&tone(1000.0), 48000, 16000);
+        assert!((rms(&passed) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.05);
+
+        let aliased = resample(&tone(12000.0), 48000, 16000);
+        assert_eq!(aliased.len(), 1600);
+        assert!(rms(&aliased) < 0.01);
+    }
+
+    #[test]
+    fn test_normalize_stereo_24_bit_wav() -> Result<()> {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 24,
+            sample_format: SampleFormat::Int,
+        };
+        let audio = wav(spec, 4800, |_| 1 << 22)?;
+
+        let samples = normalize_wav(&audio)?;
+        assert_eq!(samples.len(), 1600);
+        assert!(samples.iter().all(|&sample| (sample - 0.5).abs() < 1e-6));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_keeps_16_khz_mono() -> Result<()> {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: TARGET_SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let audio = wav(spec, 100, |i| i * 100)?;
+
+        let samples = normalize_wav(&audio)?;
+        assert_eq!(samples.len(), 100);
+        assert!((samples[1] - 100.0 / 32768.0).abs() < 1e-6);
+
+        let roundtrip = normalize_wav(&encode_wav(&samples)?)?;
+        assert_eq!(roundtrip, samples);
+
+        Ok(())
+    }
+}
@@ -1,10 +1,13 @@
 use super::enums::{
-    GeocodingImplementation, LlmImplementation, ParsingImplementation, RecordingImplementation,
-    ResponseKind, SynthesisImplementation, TranscriptionImplementation, WeatherImplementation,
+    GeocodingImplementation, LlmImplementation, ParsingImplementation, PlaybackImplementation,
+    RecordingImplementation, ResponseKind, SynthesisImplementation, TimerImplementation,
+    TranscriptionImplementation, WakeWordAction, WakeWordImplementation, WeatherImplementation,
 };
 use crate::error::Result;
 use config::{Config, File};
-use serde::Deserialize;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 use tokio::fs::{read_to_string, write};
 use toml::{to_string, Value};
@@ -17,6 +20,7 @@ pub struct AppConfig {
     pub recording: RecordingConfig,
     pub response: ResponseConfig,
     pub server: ServerConfig,
+    pub timer: TimerConfig,
     pub transcription: TranscriptionConfig,
     pub synthesis: SynthesisConfig,
     pub weather: WeatherConfig,
@@ -44,19 +48,81 @@ pub struct ParsingConfig {
     pub implementation: ParsingImplementation,
 }
 
+// Mirrors the flat [recording] table, where each optional stage has its own switch.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Deserialize)]
 pub struct RecordingConfig {
     pub device_name: String,
     pub implementation: RecordingImplementation,
     pub remote_url: String,
-    pub porcupine_sensitivity: f32,
-    pub wake_word: String,
     pub wake_word_enabled: bool,
+    pub wake_word_implementation: WakeWordImplementation,
+    pub wake_words: Vec<WakeWordConfig>,
+    pub pre_roll_ms: u64,
+    pub strip_keyword: bool,
+    pub high_pass_enabled: bool,
+    pub high_pass_cutoff_hz: f32,
+    pub noise_gate_enabled: bool,
+    pub noise_gate_threshold: f32,
+    pub agc_enabled: bool,
+    pub agc_target_level: f32,
+    pub vad_enabled: bool,
+    pub vad_threshold: f32,
+    pub vad_hang_time_ms: u64,
+    pub vad_no_speech_timeout_ms: u64,
+    pub vad_barge_in: bool,
+    // Keys from before several wake words were supported, folded into wake_words on load.
+    #[serde(default)]
+    wake_word: Option<String>,
+    #[serde(default)]
+    porcupine_sensitivity: Option<f32>,
+}
+
+impl RecordingConfig {
+    fn migrate_legacy_wake_word(&mut self) {
+        if let Some(path) = self.wake_word.take() {
+            warn!("recording.wake_word is deprecated, use [[recording.wake_words]] instead");
+            let sensitivity = self
+                .porcupine_sensitivity
+                .or_else(|| {
+                    self.wake_words
+                        .first()
+                        .map(|wake_word| wake_word.sensitivity)
+                })
+                .unwrap_or(1.0);
+            self.wake_words = vec![WakeWordConfig {
+                path,
+                sensitivity,
+                action: WakeWordAction::default(),
+                phrase: None,
+            }];
+        }
+
+        if let Some(sensitivity) = self.porcupine_sensitivity.take() {
+            warn!("recording.porcupine_sensitivity is deprecated, set sensitivity per wake word instead");
+            for wake_word in &mut self.wake_words {
+                wake_word.sensitivity = sensitivity;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WakeWordConfig {
+    pub path: String,
+    pub sensitivity: f32,
+    #[serde(default)]
+    pub action: WakeWordAction,
+    #[serde(default)]
+    pub phrase: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ResponseConfig {
     pub response_kind: ResponseKind,
+    pub playback_device: String,
+    pub playback_file_path: String,
+    pub playback_implementation: PlaybackImplementation,
 }
 
 #[derive(Debug, Deserialize)]
@@ -65,11 +131,28 @@ pub struct ServerConfig {
     pub port: u16,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TimerConfig {
+    pub implementation: TimerImplementation,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TranscriptionConfig {
     pub deepgram_base_url: String,
+    pub openai_base_url: String,
+    pub openai_model: String,
+    pub openai_api_key: String,
     pub local_model_path: String,
     pub local_use_gpu: bool,
+    pub local_language: String,
+    pub local_translate: bool,
+    pub local_beam_size: usize,
+    pub local_threads: usize,
+    pub local_temperature_increment: f32,
+    pub local_initial_prompt: String,
+    pub vocabulary: Vec<String>,
+    pub vocabulary_threshold: f32,
+    pub streaming: bool,
     pub implementation: TranscriptionImplementation,
 }
 
@@ -89,6 +172,29 @@ pub struct WeatherConfig {
     pub implementation: WeatherImplementation,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigEntry {
+    pub table: String,
+    pub key: String,
+    pub value: String,
+}
+
+impl ConfigEntry {
+    pub fn new(table: impl Into<String>, key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}={}", self.table, self.key, self.value)
+    }
+}
+
 impl AppConfig {
     fn get_config_file() -> Option<PathBuf> {
         std::env::var("XDG_CONFIG_HOME")
@@ -120,11 +226,16 @@ impl AppConfig {
             builder = builder.add_source(File::from(config_path).required(false));
         }
 
-        let config = builder.build()?;
-        Ok(config.try_deserialize()?)
+        Self::from_config(builder.build()?)
     }
 
-    pub async fn get_all_config_entries() -> Result<Vec<String>> {
+    fn from_config(config: Config) -> Result<Self> {
+        let mut app_config: Self = config.try_deserialize()?;
+        app_config.recording.migrate_legacy_wake_word();
+        Ok(app_config)
+    }
+
+    pub async fn get_all_config_entries() -> Result<Vec<ConfigEntry>> {
         let mut builder = Config::builder().add_source(File::from_str(
             include_str!("default.toml"),
             config::FileFormat::Toml,
@@ -156,7 +267,7 @@ impl AppConfig {
                         Value::Boolean(b) => b.to_string(),
                         other => other.to_string(),
                     };
-                    entries.push(format!("{}.{}={}", table_name, key, val_str));
+                    entries.push(ConfigEntry::new(table_name, key, val_str));
                 }
             }
         }
@@ -171,8 +282,7 @@ impl AppConfig {
 
             let table_value = config_value.get_mut(table).ok_or_else(|| {
                 crate::error::Error::ConfigError(config::ConfigError::Message(format!(
-                    "Table not found: {}",
-                    table
+                    "Table not found: {table}"
                 )))
             })?;
 
@@ -189,3 +299,46 @@ impl AppConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FileFormat;
+
+    fn config_with(overrides: &str) -> Result<AppConfig> {
+        AppConfig::from_config(
+            Config::builder()
+                .add_source(File::from_str(
+                    include_str!("default.toml"),
+                    FileFormat::Toml,
+                ))
+                .add_source(File::from_str(overrides, FileFormat::Toml))
+                .build()?,
+        )
+    }
+
+    #[test]
+    fn test_legacy_wake_word_keys() -> Result<()> {
+        let config =
+            config_with("[recording]\nwake_word = \"jarvis.ppn\"\nporcupine_sensitivity = 0.5\n")?;
+
+        assert_eq!(config.recording.wake_words.len(), 1);
+        assert_eq!(config.recording.wake_words[0].path, "jarvis.ppn");
+        assert!((config.recording.wake_words[0].sensitivity - 0.5).abs() < f32::EPSILON);
+
+        let config = config_with("[recording]\nporcupine_sensitivity = 0.3\n")?;
+        let defaults = config_with("")?;
+
+        assert_eq!(
+            config.recording.wake_words.len(),
+            defaults.recording.wake_words.len()
+        );
+        assert!(config
+            .recording
+            .wake_words
+            .iter()
+            .all(|wake_word| (wake_word.sensitivity - 0.3).abs() < f32::EPSILON));
+
+        Ok(())
+    }
+}
@@ -20,6 +20,13 @@ pub enum ParsingImplementation {
     Rasa,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackImplementation {
+    File,
+    Local,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RecordingImplementation {
@@ -27,11 +34,19 @@ pub enum RecordingImplementation {
     Remote,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerImplementation {
+    File,
+    Memory,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TranscriptionImplementation {
     Deepgram,
     Local,
+    OpenAiCompatible,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,6 +56,14 @@ pub enum SynthesisImplementation {
     Piper,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WakeWordImplementation {
+    None,
+    Porcupine,
+    Template,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WeatherImplementation {
@@ -53,3 +76,12 @@ pub enum ResponseKind {
     Audio,
     Text,
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WakeWordAction {
+    #[default]
+    Turn,
+    Llm,
+    Stop,
+}
@@ -1,4 +1,4 @@
 pub mod configs;
 pub mod enums;
 
-pub use configs::AppConfig;
+pub use configs::{AppConfig, ConfigEntry, WakeWordConfig};
@@ -2,18 +2,28 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("Alarm {0} not found.")]
+    AlarmNotFound(u64),
+    #[error("Alarm schedule error: {0}")]
+    AlarmSchedule(String),
     #[error("API error: {0}")]
     ApiError(String),
     #[error("Audio Codec error: {0}")]
     AudioCodec(String),
+    #[error("Audio decoding error: {0}")]
+    AudioDecoding(#[from] symphonia::core::errors::Error),
     #[error("Audio input device with the name {0} not found.")]
     AudioInputDeviceNotFound(String),
     #[error("Audio input devices error: {0}")]
     AudioInputDevices(#[from] cpal::DevicesError),
+    #[error("Audio output device with the name {0} not found.")]
+    AudioOutputDeviceNotFound(String),
     #[error("Audio processing error: {0}")]
     AudioProcessing(#[from] hound::Error),
     #[error("Audio stream build error: {0}")]
     AudioStreamBuild(#[from] cpal::BuildStreamError),
+    #[error("Audio stream config error: {0}")]
+    AudioStreamConfig(#[from] cpal::DefaultStreamConfigError),
     #[error("Audio stream error: {0}")]
     AudioStreamError(#[from] cpal::StreamError),
     #[error("Config error: {0}")]
@@ -22,6 +32,10 @@ pub enum Error {
     ConfigReadError(#[from] toml::de::Error),
     #[error("Config write error: {0}")]
     ConfigWriteError(#[from] toml::ser::Error),
+    #[error("Data directory not found.")]
+    DataDirectoryNotFound,
+    #[error("This recorder does not support selecting input devices.")]
+    DeviceSelectionUnsupported,
     #[error("Environment variable error: {0}")]
     EnvVarError(#[from] std::env::VarError),
     #[error("Geocoding error: {0}")]
@@ -40,18 +54,40 @@ pub enum Error {
     PauseAudioStream(#[from] cpal::PauseStreamError),
     #[error("Play audio stream error: {0}")]
     PlayAudioStream(#[from] cpal::PlayStreamError),
+    #[error("Playback error: {0}")]
+    Playback(String),
+    #[error("Remote recorder disconnected, the recording was lost.")]
+    RecorderDisconnected,
+    #[error("Remote recorder did not send the recording within {0:?}.")]
+    RecorderTimeout(std::time::Duration),
+    #[error("The recording belongs to another client.")]
+    RecordingInUse,
     #[error("Request error: {0}")]
     RequestError(#[from] reqwest::Error),
+    #[error("Timer {0} not found.")]
+    TimerNotFound(u64),
+    #[error("Transcription error: {0}")]
+    Transcription(String),
+    #[error("Turn cancelled.")]
+    TurnCancelled,
     #[error("Url parse error: {0}")]
     UrlParseError(#[from] url::ParseError),
     #[error("Volume adjustment error: {0}")]
     VolumeAdjustmentError(String),
+    #[cfg(feature = "porcupine")]
     #[error("Wake word error: {0}")]
     WakeWordError(#[from] porcupine::PorcupineError),
     #[error("WebSocket communication error: {0}")]
-    WebSocketError(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
     #[error("Whisper error: {0}")]
     WhisperError(#[from] whisper_rs::WhisperError),
     #[error("Workspace management error: {0}")]
     WorkspaceManagementError(String),
 }
+
+// Boxed because the WebSocket error is several times larger than every other variant.
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocketError(Box::new(error))
+    }
+}
@@ -1,35 +1,60 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+mod audio;
 mod config;
 mod error;
 mod model;
 mod server;
 mod service;
 
+use crate::audio::TARGET_SAMPLE_RATE;
 use crate::error::Result;
 use config::{
     enums::{
-        GeocodingImplementation, LlmImplementation, ParsingImplementation, RecordingImplementation,
-        SynthesisImplementation, TranscriptionImplementation, WeatherImplementation,
+        GeocodingImplementation, LlmImplementation, ParsingImplementation, PlaybackImplementation,
+        RecordingImplementation, SynthesisImplementation, TimerImplementation,
+        TranscriptionImplementation, WakeWordImplementation, WeatherImplementation,
     },
     AppConfig,
 };
+use directories::ProjectDirs;
 use log::{error, info, warn};
-use server::ws::WsServer;
+use model::event::Event;
+use server::{
+    headless::HeadlessDriver,
+    pipeline::{Pipeline, PipelineOptions},
+    ws::WsServer,
+};
+#[cfg(feature = "porcupine")]
+use service::recording::PorcupineDetector;
 use service::{
     geocoding::{GeocodingService, NominatimClient},
     llm::{deepseek_client::DeepSeekClient, LlmService, OllamaClient},
     parsing::{ParsingService, PatternMatchParser, RasaClient},
-    recording::{remote_recorder::RemoteRecorder, LocalRecorder, RecordingService},
+    playback::{FilePlayer, LocalPlayer, PlaybackService},
+    recording::{
+        dsp::{AutomaticGainControl, DspChain, HighPassFilter, NoiseGate},
+        remote_recorder::RemoteRecorder,
+        LocalRecorder, NoopDetector, RecorderOptions, RecordingService, TemplateDetector,
+        WakeWordDetector,
+    },
     runtime::LocalRuntime,
     synthesis::{ElevenLabsClient, PiperClient, SynthesizerService},
-    timer::memory_timer::MemoryTimer,
-    transcription::{DeepgramClient, LocalWhisperClient, TranscriptionService},
+    timer::{
+        alarm_service::AlarmService, file_alarm::FileAlarm, file_timer::FileTimer,
+        memory_alarm::MemoryAlarm, memory_timer::MemoryTimer, timer_service::TimerService,
+    },
+    transcription::{
+        DecodingOptions, DeepgramClient, LocalWhisperClient, OpenAiCompatibleClient,
+        TranscriptionService, Vocabulary,
+    },
     volume::PactlClient,
     weather::{OpenWeatherMapClient, WeatherService},
     workspace::KWinClient,
 };
 use std::{env::var, process, sync::Arc};
-use tokio::time::Duration;
+use tokio::{sync::broadcast, time::Duration};
+
+const EVENT_CHANNEL_CAPACITY: usize = 32;
 
 #[tokio::main]
 async fn main() {
@@ -56,96 +81,233 @@ async fn run_server() -> Result<()> {
     let config = match AppConfig::new() {
         Ok(config) => Arc::new(config),
         Err(e) => {
-            error!("Failed to load configuration: {}", e);
+            error!("Failed to load configuration: {e}");
             process::exit(1);
         }
     };
 
+    let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
     let recorder = initialize_recorder(&config).await?;
     let transcriber = initialize_transcriber(&config).await?;
     let geocoding_service = initialize_geocoding_service(&config).await?;
     let llm_service = initialize_llm_service(&config).await?;
     let weather_service = initialize_weather_service(&config).await?;
-    let timer_service = Arc::new(MemoryTimer::new());
+    let timer_service = initialize_timer_service(&config, event_sender.clone()).await;
+    let alarm_service = initialize_alarm_service(&config, event_sender.clone()).await;
     let parsing_service = initialize_parsing_service(&config).await?;
     let volume_service = Arc::new(PactlClient);
     let workspace_service = Arc::new(KWinClient);
     let synthesis_service = initialize_synthesis_service(&config)?;
 
-    let runtime_service = Box::new(LocalRuntime::new(
+    let mut runtime = LocalRuntime::new(
+        alarm_service.clone(),
         geocoding_service,
         llm_service,
         weather_service,
-        timer_service,
+        timer_service.clone(),
         volume_service,
         workspace_service,
-    ));
+    );
+    match ProjectDirs::from("", "", "voice") {
+        Some(dirs) => {
+            runtime = runtime
+                .with_location_storage(dirs.data_dir().join("locations.json"))
+                .await;
+        }
+        None => warn!("No data directory found, locations will not be saved"),
+    }
+    let runtime_service = Arc::new(runtime);
 
-    info!("Initializing WebSocket server...");
-    let server = WsServer::new(
-        &format!("{}:{}", config.server.host, config.server.port),
+    let pipeline = Arc::new(Pipeline::new(
         recorder,
         transcriber,
         parsing_service,
         runtime_service,
         synthesis_service,
+        PipelineOptions {
+            vad_barge_in: config.recording.vad_barge_in,
+            wake_phrases: wake_phrases(&config),
+            vocabulary: vocabulary(&config),
+            streaming_transcription: config.transcription.streaming,
+        },
+    ));
+
+    info!("Initializing WebSocket server...");
+    let server = WsServer::new(
+        &format!("{}:{}", config.server.host, config.server.port),
+        pipeline.clone(),
         config.response.response_kind.clone(),
+        event_sender,
+        timer_service,
+        alarm_service,
     )
     .await?;
+    let headless = HeadlessDriver::new(pipeline.clone(), initialize_playback_service(&config));
 
     info!("Server started successfully");
-    server.listen().await
+    tokio::select! {
+        result = Arc::new(server).listen() => result,
+        result = headless.run() => result,
+        result = pipeline.watch_recording_events() => result,
+    }
 }
 
-async fn initialize_recorder(config: &Arc<AppConfig>) -> Result<Box<dyn RecordingService>> {
+fn recorder_options(config: &AppConfig) -> RecorderOptions {
+    RecorderOptions {
+        wake_word_enabled: config.recording.wake_word_enabled,
+        pre_roll: Duration::from_millis(config.recording.pre_roll_ms),
+        dsp: initialize_dsp_chain(config),
+        vad_enabled: config.recording.vad_enabled,
+        vad_threshold: config.recording.vad_threshold,
+        vad_hang_time: Duration::from_millis(config.recording.vad_hang_time_ms),
+        vad_no_speech_timeout: Duration::from_millis(config.recording.vad_no_speech_timeout_ms),
+    }
+}
+
+async fn initialize_recorder(config: &Arc<AppConfig>) -> Result<Arc<dyn RecordingService>> {
     info!("Initializing recording service...");
     match config.recording.implementation {
-        RecordingImplementation::Local => Ok(Box::new(LocalRecorder::new(
+        RecordingImplementation::Local => Ok(Arc::new(LocalRecorder::new(
             &config.recording.device_name,
-            var("PICOVOICE_ACCESS_KEY")?,
-            &config.recording.wake_word,
-            config.recording.wake_word_enabled,
-            config.recording.porcupine_sensitivity,
-        )?)),
+            initialize_wake_word_detector(config),
+            &config.recording.wake_words,
+            recorder_options(config),
+        ))),
         RecordingImplementation::Remote => {
             match RemoteRecorder::new(&config.recording.remote_url).await {
-                Ok(recorder) => Ok(Box::new(recorder)),
+                Ok(recorder) => Ok(Arc::new(recorder)),
                 Err(e) => {
-                    error!("Failed to initialize remote recorder: {}", e);
+                    error!("Failed to initialize remote recorder: {e}");
                     warn!("Falling back to local recorder");
-                    Ok(Box::new(LocalRecorder::new(
+                    Ok(Arc::new(LocalRecorder::new(
                         &config.recording.device_name,
-                        var("PICOVOICE_ACCESS_KEY")?,
-                        &config.recording.wake_word,
-                        config.recording.wake_word_enabled,
-                        config.recording.porcupine_sensitivity,
-                    )?))
+                        initialize_wake_word_detector(config),
+                        &config.recording.wake_words,
+                        recorder_options(config),
+                    )))
                 }
             }
         }
     }
 }
 
-async fn initialize_transcriber(config: &Arc<AppConfig>) -> Result<Box<dyn TranscriptionService>> {
+fn initialize_wake_word_detector(config: &Arc<AppConfig>) -> Box<dyn WakeWordDetector> {
+    if !config.recording.wake_word_enabled {
+        info!("Wake word detection is disabled");
+        return Box::new(NoopDetector);
+    }
+    info!("Initializing wake word detector...");
+    let wake_words = &config.recording.wake_words;
+    let detector: Result<Box<dyn WakeWordDetector>> =
+        match config.recording.wake_word_implementation {
+            WakeWordImplementation::None => Ok(Box::new(NoopDetector)),
+            #[cfg(feature = "porcupine")]
+            WakeWordImplementation::Porcupine => var("PICOVOICE_ACCESS_KEY")
+                .map_err(Into::into)
+                .and_then(|access_key| PorcupineDetector::new(access_key, wake_words))
+                .map(|detector| Box::new(detector) as Box<dyn WakeWordDetector>),
+            #[cfg(not(feature = "porcupine"))]
+            WakeWordImplementation::Porcupine => {
+                warn!("Built without the porcupine feature, wake word detection is disabled");
+                Ok(Box::new(NoopDetector))
+            }
+            WakeWordImplementation::Template => TemplateDetector::new(wake_words)
+                .map(|detector| Box::new(detector) as Box<dyn WakeWordDetector>),
+        };
+
+    detector.unwrap_or_else(|e| {
+        error!("Failed to initialize wake word detector: {e}");
+        warn!("Falling back to no wake word detection");
+        Box::new(NoopDetector)
+    })
+}
+
+fn initialize_dsp_chain(config: &AppConfig) -> DspChain {
+    let recording = &config.recording;
+    DspChain::new(
+        recording
+            .high_pass_enabled
+            .then(|| HighPassFilter::new(recording.high_pass_cutoff_hz, TARGET_SAMPLE_RATE)),
+        recording
+            .noise_gate_enabled
+            .then(|| NoiseGate::new(recording.noise_gate_threshold, TARGET_SAMPLE_RATE)),
+        recording
+            .agc_enabled
+            .then(|| AutomaticGainControl::new(recording.agc_target_level, TARGET_SAMPLE_RATE)),
+    )
+}
+
+fn wake_phrases(config: &Arc<AppConfig>) -> Vec<String> {
+    if !config.recording.strip_keyword {
+        return Vec::new();
+    }
+    config
+        .recording
+        .wake_words
+        .iter()
+        .filter_map(|wake_word| wake_word.phrase.clone())
+        .collect()
+}
+
+fn vocabulary(config: &Arc<AppConfig>) -> Vocabulary {
+    let wake_phrases = config
+        .recording
+        .wake_words
+        .iter()
+        .filter_map(|wake_word| wake_word.phrase.clone());
+    Vocabulary::new(
+        config
+            .transcription
+            .vocabulary
+            .iter()
+            .cloned()
+            .chain(wake_phrases),
+        config.transcription.vocabulary_threshold,
+    )
+}
+
+async fn initialize_transcriber(config: &Arc<AppConfig>) -> Result<Arc<dyn TranscriptionService>> {
     info!("Initializing transcription service...");
     match config.transcription.implementation {
         TranscriptionImplementation::Deepgram => {
             match DeepgramClient::new(&config.transcription.deepgram_base_url) {
-                Ok(client) => Ok(Box::new(client)),
+                Ok(client) => Ok(Arc::new(client)),
                 Err(e) => {
-                    error!("Failed to initialize Deepgram client: {}", e);
+                    error!("Failed to initialize Deepgram client: {e}");
                     warn!("Falling back to local transcription");
-                    Ok(Box::new(LocalWhisperClient::new(
+                    Ok(Arc::new(LocalWhisperClient::new(
                         &config.transcription.local_model_path,
                         config.transcription.local_use_gpu,
+                        decoding_options(config),
                     )?))
                 }
             }
         }
-        TranscriptionImplementation::Local => Ok(Box::new(LocalWhisperClient::new(
+        TranscriptionImplementation::Local => Ok(Arc::new(LocalWhisperClient::new(
             &config.transcription.local_model_path,
             config.transcription.local_use_gpu,
+            decoding_options(config),
         )?)),
+        TranscriptionImplementation::OpenAiCompatible => {
+            let api_key = &config.transcription.openai_api_key;
+            Ok(Arc::new(OpenAiCompatibleClient::new(
+                &config.transcription.openai_base_url,
+                &config.transcription.openai_model,
+                (!api_key.is_empty()).then(|| api_key.clone()),
+            )?))
+        }
+    }
+}
+
+fn decoding_options(config: &Arc<AppConfig>) -> DecodingOptions {
+    DecodingOptions {
+        language: config.transcription.local_language.clone(),
+        translate: config.transcription.local_translate,
+        beam_size: config.transcription.local_beam_size,
+        threads: config.transcription.local_threads,
+        temperature_increment: config.transcription.local_temperature_increment,
+        initial_prompt: config.transcription.local_initial_prompt.clone(),
     }
 }
 
@@ -168,7 +330,7 @@ async fn initialize_llm_service(config: &Arc<AppConfig>) -> Result<Arc<dyn LlmSe
             match DeepSeekClient::new(&config.llm.deepseek_model, &config.llm.deepseek_base_url) {
                 Ok(client) => Ok(Arc::new(client)),
                 Err(e) => {
-                    error!("Failed to initialize DeepSeek client: {}", e);
+                    error!("Failed to initialize DeepSeek client: {e}");
                     warn!("Falling back to Ollama");
                     Ok(Arc::new(OllamaClient::new(
                         &config.llm.ollama_model,
@@ -184,12 +346,60 @@ async fn initialize_llm_service(config: &Arc<AppConfig>) -> Result<Arc<dyn LlmSe
     }
 }
 
+fn initialize_playback_service(config: &Arc<AppConfig>) -> Arc<dyn PlaybackService> {
+    info!("Initializing playback service...");
+    match config.response.playback_implementation {
+        PlaybackImplementation::File => {
+            Arc::new(FilePlayer::new(&config.response.playback_file_path))
+        }
+        PlaybackImplementation::Local => {
+            Arc::new(LocalPlayer::new(&config.response.playback_device))
+        }
+    }
+}
+
+async fn initialize_timer_service(
+    config: &Arc<AppConfig>,
+    event_sender: broadcast::Sender<Event>,
+) -> Arc<dyn TimerService> {
+    info!("Initializing timer service...");
+    match config.timer.implementation {
+        TimerImplementation::File => match FileTimer::new(event_sender.clone()).await {
+            Ok(timer) => Arc::new(timer),
+            Err(e) => {
+                error!("Failed to initialize file timer: {e}");
+                warn!("Falling back to in-memory timers");
+                Arc::new(MemoryTimer::new(event_sender))
+            }
+        },
+        TimerImplementation::Memory => Arc::new(MemoryTimer::new(event_sender)),
+    }
+}
+
+async fn initialize_alarm_service(
+    config: &Arc<AppConfig>,
+    event_sender: broadcast::Sender<Event>,
+) -> Arc<dyn AlarmService> {
+    info!("Initializing alarm service...");
+    match config.timer.implementation {
+        TimerImplementation::File => match FileAlarm::new(event_sender.clone()).await {
+            Ok(alarm) => Arc::new(alarm),
+            Err(e) => {
+                error!("Failed to initialize file alarms: {e}");
+                warn!("Falling back to in-memory alarms");
+                Arc::new(MemoryAlarm::new(event_sender))
+            }
+        },
+        TimerImplementation::Memory => Arc::new(MemoryAlarm::new(event_sender)),
+    }
+}
+
 async fn initialize_weather_service(config: &Arc<AppConfig>) -> Result<Arc<dyn WeatherService>> {
     info!("Initializing weather service...");
     match config.weather.implementation {
         WeatherImplementation::OpenWeatherMap => {
             let api_key = var("OPENWEATHERMAP_API_KEY").map_err(|e| {
-                error!("OpenWeatherMap API key not found: {}", e);
+                error!("OpenWeatherMap API key not found: {e}");
                 e
             })?;
             Ok(Arc::new(OpenWeatherMapClient::new(
@@ -200,22 +410,22 @@ async fn initialize_weather_service(config: &Arc<AppConfig>) -> Result<Arc<dyn W
     }
 }
 
-async fn initialize_parsing_service(config: &Arc<AppConfig>) -> Result<Box<dyn ParsingService>> {
+async fn initialize_parsing_service(config: &Arc<AppConfig>) -> Result<Arc<dyn ParsingService>> {
     info!("Initializing parsing service...");
     match config.parsing.implementation {
-        ParsingImplementation::PatternMatch => Ok(Box::new(PatternMatchParser::new())),
+        ParsingImplementation::PatternMatch => Ok(Arc::new(PatternMatchParser::new())),
         ParsingImplementation::Rasa => match RasaClient::new(&config.parsing.rasa_base_url) {
-            Ok(client) => Ok(Box::new(client)),
+            Ok(client) => Ok(Arc::new(client)),
             Err(e) => {
-                error!("Failed to initialize Rasa client: {}", e);
+                error!("Failed to initialize Rasa client: {e}");
                 warn!("Falling back to pattern matching parser");
-                Ok(Box::new(PatternMatchParser::new()))
+                Ok(Arc::new(PatternMatchParser::new()))
             }
         },
     }
 }
 
-fn initialize_synthesis_service(config: &Arc<AppConfig>) -> Result<Box<dyn SynthesizerService>> {
+fn initialize_synthesis_service(config: &Arc<AppConfig>) -> Result<Arc<dyn SynthesizerService>> {
     info!("Initializing synthesis service...");
     match config.synthesis.implementation {
         SynthesisImplementation::Elevenlabs => {
@@ -224,18 +434,18 @@ fn initialize_synthesis_service(config: &Arc<AppConfig>) -> Result<Box<dyn Synth
                 &config.synthesis.elevenlabs_model_id,
                 &config.synthesis.elevenlabs_voice_id,
             ) {
-                Ok(client) => Ok(Box::new(client)),
+                Ok(client) => Ok(Arc::new(client)),
                 Err(e) => {
-                    error!("Failed to initialize ElevenLabs client: {}", e);
+                    error!("Failed to initialize ElevenLabs client: {e}");
                     warn!("Falling back to Piper");
-                    Ok(Box::new(PiperClient::new(
+                    Ok(Arc::new(PiperClient::new(
                         &config.synthesis.piper_base_url,
                         &config.synthesis.piper_voice,
                     )?))
                 }
             }
         }
-        SynthesisImplementation::Piper => Ok(Box::new(PiperClient::new(
+        SynthesisImplementation::Piper => Ok(Arc::new(PiperClient::new(
             &config.synthesis.piper_base_url,
             &config.synthesis.piper_voice,
         )?)),
@@ -1,14 +1,17 @@
 use serde::{
     de::{Error, Visitor},
-    Deserialize, Deserializer,
+    Deserialize, Deserializer, Serialize, Serializer,
 };
 use std::fmt;
+use std::time::Duration;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Action {
     pub intent: Intent,
     pub entities: Vec<Entity>,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_confidence: Option<f32>,
 }
 
 impl Action {
@@ -17,27 +20,37 @@ impl Action {
             intent,
             entities,
             text: text.into(),
+            transcript_confidence: None,
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Intent {
     pub name: IntentKind,
     pub confidence: Option<f32>,
 }
 
 impl Intent {
-    pub fn new(name: IntentKind, confidence: Option<f32>) -> Intent {
+    pub const fn new(name: IntentKind, confidence: Option<f32>) -> Intent {
         Intent { name, confidence }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IntentKind {
     LlmQuery,
 
     SetTimer,
+    ListTimers,
+    CancelTimer,
+    PauseTimer,
+    ResumeTimer,
+    TimerRemaining,
+
+    SetAlarm,
+    ListAlarms,
+    CancelAlarm,
 
     WeatherQuery,
 
@@ -81,6 +94,14 @@ impl<'de> Deserialize<'de> for IntentKind {
                     "maximize_window" => Ok(IntentKind::MaximizeWindow),
                     "minimize_window" => Ok(IntentKind::MinimizeWindow),
                     "set_timer" => Ok(IntentKind::SetTimer),
+                    "list_timers" => Ok(IntentKind::ListTimers),
+                    "cancel_timer" => Ok(IntentKind::CancelTimer),
+                    "pause_timer" => Ok(IntentKind::PauseTimer),
+                    "resume_timer" => Ok(IntentKind::ResumeTimer),
+                    "timer_remaining" => Ok(IntentKind::TimerRemaining),
+                    "set_alarm" => Ok(IntentKind::SetAlarm),
+                    "list_alarms" => Ok(IntentKind::ListAlarms),
+                    "cancel_alarm" => Ok(IntentKind::CancelAlarm),
                     "show_desktop" => Ok(IntentKind::ShowDesktop),
                     "switch_workspace" => Ok(IntentKind::SwitchWorkspace),
                     "weather_query" => Ok(IntentKind::WeatherQuery),
@@ -93,7 +114,38 @@ impl<'de> Deserialize<'de> for IntentKind {
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl Serialize for IntentKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name = match self {
+            Self::CloseWindow => "close_window",
+            Self::LlmQuery => "nlu_fallback",
+            Self::IncreaseVolume => "increase_volume",
+            Self::DecreaseVolume => "decrease_volume",
+            Self::SetVolume => "set_volume",
+            Self::MaximizeWindow => "maximize_window",
+            Self::MinimizeWindow => "minimize_window",
+            Self::SetTimer => "set_timer",
+            Self::ListTimers => "list_timers",
+            Self::CancelTimer => "cancel_timer",
+            Self::PauseTimer => "pause_timer",
+            Self::ResumeTimer => "resume_timer",
+            Self::TimerRemaining => "timer_remaining",
+            Self::SetAlarm => "set_alarm",
+            Self::ListAlarms => "list_alarms",
+            Self::CancelAlarm => "cancel_alarm",
+            Self::ShowDesktop => "show_desktop",
+            Self::SwitchWorkspace => "switch_workspace",
+            Self::WeatherQuery => "weather_query",
+            Self::Other(value) => value,
+        };
+        serializer.serialize_str(name)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Entity {
     pub entity: String,
     pub value: EntityValue,
@@ -101,20 +153,38 @@ pub struct Entity {
     pub confidence: Option<f32>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(untagged)]
 pub enum EntityValue {
     Index(usize),
     Duration(DurationValue),
+    Time(TimeValue),
     String(String),
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 pub struct DurationValue {
     pub value: u64,
     pub unit: String,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TimeValue {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl DurationValue {
+    pub fn to_duration(&self) -> Duration {
+        let multiplier = match self.unit.trim_end_matches('s') {
+            "hour" => 3600,
+            "minute" => 60,
+            _ => 1,
+        };
+        Duration::from_secs(self.value * multiplier)
+    }
+}
+
 impl Entity {
     pub fn new(entity: impl Into<String>, value: EntityValue, confidence: Option<f32>) -> Entity {
         Entity {
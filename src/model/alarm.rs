@@ -0,0 +1,131 @@
+use jiff::civil::{Time, Weekday};
+use jiff::{ToSpan, Zoned};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AlarmSchedule {
+    pub time: Time,
+    #[serde(with = "weekdays")]
+    pub days: Vec<Weekday>,
+    pub recurring: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Alarm {
+    pub id: u64,
+    pub schedule: AlarmSchedule,
+    pub description: String,
+    pub next: Zoned,
+}
+
+impl AlarmSchedule {
+    pub const fn new(time: Time, days: Vec<Weekday>, recurring: bool) -> Self {
+        Self {
+            time,
+            days,
+            recurring,
+        }
+    }
+
+    pub fn next_after(&self, after: &Zoned) -> Option<Zoned> {
+        (0..=7).find_map(|offset| {
+            let date = after.date().checked_add(offset.days()).ok()?;
+            if !self.days.is_empty() && !self.days.contains(&date.weekday()) {
+                return None;
+            }
+            let candidate = date
+                .to_datetime(self.time)
+                .to_zoned(after.time_zone().clone())
+                .ok()?;
+            (candidate > *after).then_some(candidate)
+        })
+    }
+}
+
+// Weekdays are stored as numbers from 1 (Monday) to 7 (Sunday).
+mod weekdays {
+    use jiff::civil::Weekday;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(days: &[Weekday], serializer: S) -> Result<S::Ok, S::Error> {
+        days.iter()
+            .map(|day| day.to_monday_one_offset())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Weekday>, D::Error> {
+        Vec::<i8>::deserialize(deserializer)?
+            .into_iter()
+            .map(|day| Weekday::from_monday_one_offset(day).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::{date, time};
+    use jiff::tz::TimeZone;
+
+    fn friday_evening() -> Zoned {
+        date(2025, 3, 7)
+            .at(20, 0, 0, 0)
+            .to_zoned(TimeZone::UTC)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_alarm_schedule_later_today() {
+        let schedule = AlarmSchedule::new(time(21, 30, 0, 0), Vec::new(), false);
+        let next = schedule.next_after(&friday_evening()).unwrap();
+        assert_eq!(next.datetime(), date(2025, 3, 7).at(21, 30, 0, 0));
+    }
+
+    #[test]
+    fn test_alarm_schedule_rolls_over_to_tomorrow() {
+        let schedule = AlarmSchedule::new(time(7, 30, 0, 0), Vec::new(), false);
+        let next = schedule.next_after(&friday_evening()).unwrap();
+        assert_eq!(next.datetime(), date(2025, 3, 8).at(7, 30, 0, 0));
+    }
+
+    #[test]
+    fn test_alarm_schedule_skips_to_next_weekday() {
+        let weekdays = vec![
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ];
+        let schedule = AlarmSchedule::new(time(8, 0, 0, 0), weekdays, true);
+        let next = schedule.next_after(&friday_evening()).unwrap();
+        assert_eq!(next.datetime(), date(2025, 3, 10).at(8, 0, 0, 0));
+        let after = schedule.next_after(&next).unwrap();
+        assert_eq!(after.datetime(), date(2025, 3, 11).at(8, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_alarm_schedule_serialization() {
+        let schedule = AlarmSchedule::new(
+            time(6, 45, 0, 0),
+            vec![Weekday::Monday, Weekday::Sunday],
+            true,
+        );
+        let json = serde_json::to_value(&schedule).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"time": "06:45:00", "days": [1, 7], "recurring": true})
+        );
+        assert_eq!(
+            serde_json::from_value::<AlarmSchedule>(json).unwrap(),
+            schedule
+        );
+        assert!(serde_json::from_value::<AlarmSchedule>(
+            serde_json::json!({"time": "06:45:00", "days": [8], "recurring": true})
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+pub const SEQUENCE_LENGTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PcmFormat {
+    I16,
+    F32,
+}
+
+impl PcmFormat {
+    pub const fn sample_size(self) -> usize {
+        match self {
+            Self::I16 => 2,
+            Self::F32 => 4,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: PcmFormat,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AudioStreamMessage {
+    AudioFormat(AudioFormat),
+    AudioEnd { frames: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioFrame {
+    pub sequence: u32,
+    pub samples: Vec<f32>,
+}
+
+impl AudioFrame {
+    pub fn decode(data: &[u8], format: PcmFormat) -> Result<Self> {
+        if data.len() < SEQUENCE_LENGTH {
+            return Err(Error::AudioCodec("Audio frame is too short".to_string()));
+        }
+        let (sequence, payload) = data.split_at(SEQUENCE_LENGTH);
+        let sequence = u32::from_le_bytes([sequence[0], sequence[1], sequence[2], sequence[3]]);
+
+        if payload.len() % format.sample_size() != 0 {
+            return Err(Error::AudioCodec(format!(
+                "Audio frame {} has {} bytes, not a whole number of samples",
+                sequence,
+                payload.len()
+            )));
+        }
+        let samples = match format {
+            PcmFormat::I16 => payload
+                .chunks_exact(2)
+                .map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32768.0)
+                .collect(),
+            PcmFormat::F32 => payload
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        };
+        Ok(Self { sequence, samples })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_str, json, to_value};
+
+    #[test]
+    fn test_audio_stream_messages() -> Result<()> {
+        let message: AudioStreamMessage = from_str(
+            r#"{"type": "audio_format", "sample_rate": 48000, "channels": 2, "sample_format": "i16"}"#,
+        )?;
+        assert_eq!(
+            message,
+            AudioStreamMessage::AudioFormat(AudioFormat {
+                sample_rate: 48000,
+                channels: 2,
+                sample_format: PcmFormat::I16,
+            })
+        );
+        assert_eq!(
+            to_value(AudioStreamMessage::AudioEnd { frames: 12 })?,
+            json!({"type": "audio_end", "frames": 12})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_audio_frame_decode() -> Result<()> {
+        let mut data = 7u32.to_le_bytes().to_vec();
+        data.extend(16384i16.to_le_bytes());
+        data.extend((-32768i16).to_le_bytes());
+        data.extend(0i16.to_le_bytes());
+
+        let frame = AudioFrame::decode(&data, PcmFormat::I16)?;
+        assert_eq!(frame.sequence, 7);
+        assert_eq!(frame.samples, vec![0.5, -1.0, 0.0]);
+
+        assert!(AudioFrame::decode(&data[..5], PcmFormat::I16).is_err());
+        assert!(AudioFrame::decode(&data, PcmFormat::F32).is_err());
+        Ok(())
+    }
+}
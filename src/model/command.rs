@@ -7,6 +7,9 @@ pub enum Command {
     StopRecording,
     GetConfig,
     SetConfig(String),
+    ListDevices,
+    SelectDevice(String),
+    GetRecorderStatus,
     Unknown(String),
 }
 
@@ -23,7 +26,10 @@ impl From<&str> for Command {
             "AI" => Self::StartRecording,
             "AT" => Self::StopRecording,
             "G" => Self::GetConfig,
+            "D" => Self::ListDevices,
+            "S" => Self::GetRecorderStatus,
             x if x.starts_with('C') => Self::SetConfig(x.strip_prefix('C').unwrap().to_owned()),
+            x if x.starts_with('D') => Self::SelectDevice(x.strip_prefix('D').unwrap().to_owned()),
             other => Self::Unknown(other.to_string()),
         }
     }
@@ -36,7 +42,10 @@ impl From<Command> for String {
             Command::StartRecording => "AI".to_string(),
             Command::StopRecording => "AT".to_string(),
             Command::GetConfig => "G".to_string(),
-            Command::SetConfig(s) => format!("C{}", s),
+            Command::SetConfig(s) => format!("C{s}"),
+            Command::ListDevices => "D".to_string(),
+            Command::SelectDevice(s) => format!("D{s}"),
+            Command::GetRecorderStatus => "S".to_string(),
             Command::Unknown(s) => s,
         }
     }
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    AlarmFired { description: String },
+    AlarmMissed { description: String },
+    TimerCompleted { description: String },
+    TimerMissed { description: String },
+}
@@ -1,5 +1,12 @@
 pub mod action;
+pub mod alarm;
+pub mod audio_stream;
 pub mod command;
+pub mod device;
+pub mod event;
 pub mod geocode;
+pub mod protocol;
+pub mod recording;
 pub mod timer;
+pub mod transcription;
 pub mod weather;
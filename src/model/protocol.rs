@@ -0,0 +1,318 @@
+use crate::config::ConfigEntry;
+use crate::model::action::Action;
+use crate::model::device::InputDevice;
+use crate::model::event::Event;
+use crate::model::recording::RecorderStatus;
+use crate::model::transcription::{Alternative, Word};
+use serde::{Deserialize, Serialize};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+pub struct ClientMessage {
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub kind: ClientMessageKind,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessageKind {
+    Hello {
+        version: u32,
+    },
+    Cancel,
+    StartRecording,
+    StopRecording,
+    GetConfig,
+    SetConfig {
+        table: String,
+        key: String,
+        value: String,
+    },
+    ListDevices,
+    SelectDevice {
+        name: String,
+    },
+    GetRecorderStatus,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServerMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub kind: ServerMessageKind,
+}
+
+impl ServerMessage {
+    pub const fn new(id: Option<u64>, kind: ServerMessageKind) -> Self {
+        Self { id, kind }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessageKind {
+    Hello {
+        version: u32,
+    },
+    PartialTranscript {
+        text: String,
+    },
+    Transcript {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        confidence: Option<f32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        words: Vec<Word>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        alternatives: Vec<Alternative>,
+    },
+    Intent(Action),
+    Response {
+        text: String,
+    },
+    AudioStart,
+    AudioEnd,
+    Cancelled,
+    Config(ConfigEntry),
+    Devices {
+        devices: Vec<InputDevice>,
+    },
+    RecorderStatus {
+        status: RecorderStatus,
+    },
+    Event(Event),
+    Error {
+        message: String,
+    },
+    Warning {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::model::action::{Entity, EntityValue, Intent, IntentKind};
+    use crate::model::device::InputConfig;
+    use serde_json::{from_str, json, to_value};
+
+    #[test]
+    fn test_client_message_set_config() -> Result<()> {
+        let message: ClientMessage = from_str(
+            r#"{"id": 7, "type": "set_config", "table": "response", "key": "response_kind", "value": "text"}"#,
+        )?;
+
+        assert_eq!(message.id, Some(7));
+        match message.kind {
+            ClientMessageKind::SetConfig { table, key, value } => {
+                assert_eq!(table, "response");
+                assert_eq!(key, "response_kind");
+                assert_eq!(value, "text");
+            }
+            other => panic!("Unexpected message kind: {other:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_message_without_id() -> Result<()> {
+        let message: ClientMessage = from_str(r#"{"type": "stop_recording"}"#)?;
+        assert_eq!(message.id, None);
+        assert!(matches!(message.kind, ClientMessageKind::StopRecording));
+        Ok(())
+    }
+
+    #[test]
+    fn test_server_message_intent() -> Result<()> {
+        let action = Action::new(
+            Intent::new(IntentKind::SwitchWorkspace, None),
+            vec![Entity::new("NUMBER", EntityValue::Index(3), None)],
+            "Switch to workspace 3",
+        );
+        let message = ServerMessage::new(Some(1), ServerMessageKind::Intent(action));
+
+        assert_eq!(
+            to_value(&message)?,
+            json!({
+                "id": 1,
+                "type": "intent",
+                "intent": { "name": "switch_workspace", "confidence": null },
+                "entities": [{ "entity": "NUMBER", "value": 3, "confidence_entity": null }],
+                "text": "Switch to workspace 3"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_server_message_transcript_starting_with_c() -> Result<()> {
+        let message = ServerMessage::new(
+            Some(2),
+            ServerMessageKind::Transcript {
+                text: "Close the window".to_string(),
+                language: None,
+                confidence: None,
+                words: Vec::new(),
+                alternatives: Vec::new(),
+            },
+        );
+
+        assert_eq!(
+            to_value(&message)?,
+            json!({ "id": 2, "type": "transcript", "text": "Close the window" })
+        );
+
+        let message = ServerMessage::new(
+            Some(2),
+            ServerMessageKind::Transcript {
+                text: "Schließe das Fenster".to_string(),
+                language: Some("de".to_string()),
+                confidence: Some(0.5),
+                words: vec![Word::new("Schließe", 0.0, 0.5, Some(0.5))],
+                alternatives: vec![Alternative::new("Schließ das Fenster", None)],
+            },
+        );
+        assert_eq!(
+            to_value(&message)?,
+            json!({
+                "id": 2,
+                "type": "transcript",
+                "text": "Schließe das Fenster",
+                "language": "de",
+                "confidence": 0.5,
+                "words": [{ "text": "Schließe", "start": 0.0, "end": 0.5, "confidence": 0.5 }],
+                "alternatives": [{ "text": "Schließ das Fenster" }]
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_server_message_audio_markers() -> Result<()> {
+        let start = ServerMessage::new(Some(3), ServerMessageKind::AudioStart);
+        let end = ServerMessage::new(Some(3), ServerMessageKind::AudioEnd);
+
+        assert_eq!(to_value(&start)?, json!({ "id": 3, "type": "audio_start" }));
+        assert_eq!(to_value(&end)?, json!({ "id": 3, "type": "audio_end" }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_server_message_event() -> Result<()> {
+        let message = ServerMessage::new(
+            None,
+            ServerMessageKind::Event(Event::TimerCompleted {
+                description: "pasta".to_string(),
+            }),
+        );
+
+        assert_eq!(
+            to_value(&message)?,
+            json!({ "type": "event", "event": "timer_completed", "description": "pasta" })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_device_messages() -> Result<()> {
+        let message: ClientMessage =
+            from_str(r#"{"id": 4, "type": "select_device", "name": "USB Microphone"}"#)?;
+        assert!(
+            matches!(message.kind, ClientMessageKind::SelectDevice { name } if name == "USB Microphone")
+        );
+
+        let message = ServerMessage::new(
+            Some(5),
+            ServerMessageKind::Devices {
+                devices: vec![InputDevice {
+                    name: "pipewire".to_string(),
+                    is_default: true,
+                    is_active: true,
+                    configs: vec![InputConfig {
+                        channels: 2,
+                        min_sample_rate: 44100,
+                        max_sample_rate: 48000,
+                        sample_format: "f32".to_string(),
+                    }],
+                }],
+            },
+        );
+        assert_eq!(
+            to_value(&message)?,
+            json!({
+                "id": 5,
+                "type": "devices",
+                "devices": [{
+                    "name": "pipewire",
+                    "is_default": true,
+                    "is_active": true,
+                    "configs": [{
+                        "channels": 2,
+                        "min_sample_rate": 44100,
+                        "max_sample_rate": 48000,
+                        "sample_format": "f32"
+                    }]
+                }]
+            })
+        );
+
+        let message = ServerMessage::new(
+            Some(4),
+            ServerMessageKind::Warning {
+                message: "Could not save the input device".to_string(),
+            },
+        );
+        assert_eq!(
+            to_value(&message)?,
+            json!({"id": 4, "type": "warning", "message": "Could not save the input device"})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recorder_status_messages() -> Result<()> {
+        let message: ClientMessage = from_str(r#"{"id": 6, "type": "get_recorder_status"}"#)?;
+        assert!(matches!(message.kind, ClientMessageKind::GetRecorderStatus));
+
+        let message = ServerMessage::new(
+            None,
+            ServerMessageKind::RecorderStatus {
+                status: RecorderStatus::Reconnecting,
+            },
+        );
+        assert_eq!(
+            to_value(&message)?,
+            json!({ "type": "recorder_status", "status": "reconnecting" })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_transcript_message() -> Result<()> {
+        let message = ServerMessage::new(
+            None,
+            ServerMessageKind::PartialTranscript {
+                text: "Close the".to_string(),
+            },
+        );
+        assert_eq!(
+            to_value(&message)?,
+            json!({ "type": "partial_transcript", "text": "Close the" })
+        );
+
+        Ok(())
+    }
+}
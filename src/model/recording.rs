@@ -0,0 +1,19 @@
+use crate::config::enums::WakeWordAction;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecorderStatus {
+    Connected,
+    Reconnecting,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordingEvent {
+    WakeWordDetected(WakeWordAction),
+    SpeechStarted,
+    SpeechEnded,
+    NoSpeech,
+    StatusChanged(RecorderStatus),
+    Error(String),
+}
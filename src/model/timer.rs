@@ -1,21 +1,43 @@
-use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Timer {
+    pub id: u64,
     pub duration: Duration,
-    pub start_time: Instant,
     pub description: String,
+    pub state: TimerState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerState {
+    Running { ends_at: SystemTime },
+    Paused { remaining: Duration },
 }
 
 impl Timer {
-    pub fn new(duration: Duration, description: String) -> Self {
+    pub fn new(id: u64, duration: Duration, description: String) -> Self {
         Self {
+            id,
             duration,
-            start_time: Instant::now(),
             description,
+            state: TimerState::Running {
+                ends_at: SystemTime::now() + duration,
+            },
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        match self.state {
+            TimerState::Running { ends_at } => ends_at
+                .duration_since(SystemTime::now())
+                .unwrap_or_default(),
+            TimerState::Paused { remaining } => remaining,
         }
     }
 
-    pub fn is_completed(&self) -> bool {
-        self.start_time.elapsed() >= self.duration
+    pub const fn is_paused(&self) -> bool {
+        matches!(self.state, TimerState::Paused { .. })
     }
 }
@@ -0,0 +1,204 @@
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Word {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl Word {
+    pub fn new(text: impl Into<String>, start: f32, end: f32, confidence: Option<f32>) -> Self {
+        Self {
+            text: text.into(),
+            start,
+            end,
+            confidence,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Alternative {
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+impl Alternative {
+    pub fn new(text: impl Into<String>, confidence: Option<f32>) -> Self {
+        Self {
+            text: text.into(),
+            confidence,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transcript {
+    pub text: String,
+    pub language: Option<String>,
+    pub confidence: Option<f32>,
+    pub words: Vec<Word>,
+    pub alternatives: Vec<Alternative>,
+}
+
+impl Transcript {
+    pub fn new(text: impl Into<String>, language: Option<String>) -> Self {
+        Self {
+            text: text.into(),
+            language,
+            ..Default::default()
+        }
+    }
+
+    pub const fn with_confidence(mut self, confidence: Option<f32>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn with_words(mut self, words: Vec<Word>) -> Self {
+        self.words = words;
+        self
+    }
+
+    pub fn with_alternatives(mut self, alternatives: Vec<Alternative>) -> Self {
+        self.alternatives = alternatives;
+        self
+    }
+
+    /*
+     * Applies a correction to the text and the alternatives.
+     * Words keep their timings when the correction respells them one for one or removes leading words,
+     * and are dropped when they no longer line up with the text.
+     */
+    pub fn rewrite(&mut self, rewrite: impl Fn(&str) -> String) {
+        let text = rewrite(&self.text);
+        let previous: Vec<&str> = self.text.split_whitespace().collect();
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        if self.words.len() == previous.len() && tokens.len() == previous.len() {
+            for (word, token) in self.words.iter_mut().zip(&tokens) {
+                word.text = (*token).to_string();
+            }
+        } else if self.words.len() == previous.len() && previous.ends_with(&tokens) {
+            self.words.drain(..previous.len() - tokens.len());
+        } else {
+            self.words.clear();
+        }
+        self.text = text;
+
+        for alternative in &mut self.alternatives {
+            alternative.text = rewrite(&alternative.text);
+        }
+    }
+
+    pub fn offset(mut self, seconds: f32) -> Self {
+        for word in &mut self.words {
+            word.start += seconds;
+            word.end += seconds;
+        }
+        self
+    }
+
+    pub fn append(&mut self, mut other: Self) {
+        other.text = other.text.trim().to_string();
+        if other.text.is_empty() {
+            return;
+        }
+        if self.text.is_empty() {
+            *self = other;
+            return;
+        }
+
+        self.text.push(' ');
+        self.text.push_str(&other.text);
+        self.language = other.language.or_else(|| self.language.take());
+        self.confidence = match (self.confidence, other.confidence) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.words.extend(other.words);
+        self.alternatives.clear();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hypothesis {
+    pub transcript: Transcript,
+    pub is_final: bool,
+}
+
+impl Hypothesis {
+    pub const fn new(transcript: Transcript, is_final: bool) -> Self {
+        Self {
+            transcript,
+            is_final,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_transcripts() {
+        let mut transcript = Transcript::default();
+        transcript.append(
+            Transcript::new("What is", Some("en".to_string()))
+                .with_confidence(Some(0.9))
+                .with_words(vec![
+                    Word::new("What", 0.0, 0.2, Some(0.9)),
+                    Word::new("is", 0.2, 0.4, Some(0.95)),
+                ])
+                .with_alternatives(vec![Alternative::new("What's", Some(0.4))]),
+        );
+        assert_eq!(transcript.alternatives.len(), 1);
+
+        transcript.append(Transcript::new(" ", None));
+        transcript.append(
+            Transcript::new("the weather?", None)
+                .with_confidence(Some(0.6))
+                .with_words(vec![
+                    Word::new("the", 0.0, 0.1, Some(0.8)),
+                    Word::new("weather?", 0.1, 0.5, Some(0.5)),
+                ])
+                .offset(0.4),
+        );
+
+        assert_eq!(transcript.text, "What is the weather?");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.confidence, Some(0.6));
+        assert_eq!(transcript.words.len(), 4);
+        assert!((transcript.words[3].start - 0.5).abs() < f32::EPSILON);
+        assert!(transcript.alternatives.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_keeps_words_in_line() {
+        let mut transcript = Transcript::new("Ferries, weather in St Polten?", None)
+            .with_words(vec![
+                Word::new("Ferries,", 0.0, 0.4, Some(0.6)),
+                Word::new("weather", 0.5, 0.8, Some(0.9)),
+                Word::new("in", 0.8, 0.9, Some(0.9)),
+                Word::new("St", 0.9, 1.0, Some(0.7)),
+                Word::new("Polten?", 1.0, 1.4, Some(0.5)),
+            ])
+            .with_alternatives(vec![Alternative::new("Ferris weather in St Polten", None)]);
+
+        transcript.rewrite(|text| text.replace("St Polten", "St. Pölten"));
+        transcript.rewrite(|text| text.replacen("Ferries, ", "", 1).replacen("Ferris ", "", 1));
+
+        assert_eq!(transcript.text, "weather in St. Pölten?");
+        let words: Vec<&str> = transcript.words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(words, ["weather", "in", "St.", "Pölten?"]);
+        assert!((transcript.words[0].start - 0.5).abs() < f32::EPSILON);
+        assert_eq!(transcript.alternatives[0].text, "weather in St. Pölten");
+
+        transcript.rewrite(|text| text.replace("St. Pölten", "Stuttgart"));
+        assert_eq!(transcript.text, "weather in Stuttgart?");
+        assert!(transcript.words.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use super::pipeline::{Pipeline, TurnOutput};
+use crate::config::enums::ResponseKind;
+use crate::error::{Error, Result};
+use crate::model::action::Action;
+use crate::model::recording::RecordingEvent;
+use crate::model::transcription::Transcript;
+use crate::service::playback::PlaybackService;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use log::{error, info, warn};
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+const HEADLESS_OWNER: u64 = 0;
+
+struct LocalOutput<'a> {
+    playback: &'a dyn PlaybackService,
+}
+
+#[async_trait]
+impl TurnOutput for LocalOutput<'_> {
+    async fn transcript(&mut self, transcript: &Transcript) -> Result<()> {
+        info!("Heard: {}", transcript.text);
+        Ok(())
+    }
+
+    async fn intent(&mut self, action: &Action) -> Result<()> {
+        info!("Running intent {:?}", action.intent.name);
+        Ok(())
+    }
+
+    async fn response(&mut self, text: String) -> Result<()> {
+        info!("Reply: {}", text);
+        Ok(())
+    }
+
+    async fn audio(&mut self, audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+        self.playback.play(audio).await
+    }
+}
+
+pub struct HeadlessDriver {
+    pipeline: Arc<Pipeline>,
+    playback: Arc<dyn PlaybackService>,
+}
+
+impl HeadlessDriver {
+    pub fn new(pipeline: Arc<Pipeline>, playback: Arc<dyn PlaybackService>) -> Self {
+        Self { pipeline, playback }
+    }
+
+    async fn run_turn(&self, events: &mut broadcast::Receiver<RecordingEvent>) -> Result<()> {
+        let mut output = LocalOutput {
+            playback: self.playback.as_ref(),
+        };
+        let turn = self
+            .pipeline
+            .run_turn(HEADLESS_OWNER, &ResponseKind::Audio, &mut output);
+        tokio::pin!(turn);
+
+        loop {
+            tokio::select! {
+                result = &mut turn => return result,
+                event = events.recv() => match event {
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Headless driver missed {skipped} recording events");
+                    }
+                    Err(RecvError::Closed) => return (&mut turn).await,
+                },
+            }
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let mut events = self.pipeline.recorder().subscribe();
+
+        loop {
+            match events.recv().await {
+                Ok(RecordingEvent::SpeechEnded) => {
+                    if !self.pipeline.claim_recording(HEADLESS_OWNER, false) {
+                        continue;
+                    }
+                    info!("Speech ended without a client, running the turn locally");
+                    let result = self.run_turn(&mut events).await;
+                    match result {
+                        Ok(()) => {}
+                        Err(Error::TurnCancelled) => {
+                            info!("Local reply interrupted");
+                            if let Err(e) = self.playback.stop().await {
+                                error!("Failed to stop playback: {e}");
+                            }
+                        }
+                        Err(e) => error!("Local turn failed: {e}"),
+                    }
+                }
+                Ok(RecordingEvent::WakeWordDetected(action)) => {
+                    info!("Wake word detected ({action:?})");
+                }
+                Ok(RecordingEvent::SpeechStarted) => {}
+                Ok(RecordingEvent::NoSpeech) => info!("No speech after the wake word"),
+                Ok(RecordingEvent::StatusChanged(status)) => {
+                    info!("Recorder status: {status:?}");
+                }
+                Ok(RecordingEvent::Error(message)) => error!("Recording error: {message}"),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Headless driver missed {skipped} recording events");
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
@@ -1 +1,3 @@
+pub mod headless;
+pub mod pipeline;
 pub mod ws;
@@ -0,0 +1,986 @@
+use crate::config::enums::{ResponseKind, WakeWordAction};
+use crate::error::{Error, Result};
+use crate::model::action::{Action, Intent, IntentKind};
+use crate::model::recording::RecordingEvent;
+use crate::model::transcription::Transcript;
+use crate::service::runtime::RuntimeService;
+use crate::service::synthesis::{synthesize_sentences, SynthesizerService};
+use crate::service::{
+    parsing::ParsingService,
+    recording::RecordingService,
+    transcription::{TranscriptionService, Vocabulary},
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, StreamExt};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+const PARTIAL_CHANNEL_CAPACITY: usize = 16;
+
+#[async_trait]
+pub trait TurnOutput: Send {
+    async fn transcript(&mut self, transcript: &Transcript) -> Result<()>;
+    async fn intent(&mut self, action: &Action) -> Result<()>;
+    async fn response(&mut self, text: String) -> Result<()>;
+    async fn audio(&mut self, audio: BoxStream<'static, Result<Bytes>>) -> Result<()>;
+
+    // Whether audio is sent sentence by sentence rather than as one reply.
+    fn streams_sentences(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+struct Turn {
+    cancel: Notify,
+    speaking: AtomicBool,
+    direct_llm: bool,
+}
+
+// The recording's owner and whether its wake word asked for the LLM directly.
+#[derive(Default)]
+struct RecordingClaim {
+    owner: Option<u64>,
+    direct_llm: bool,
+}
+
+// Registers a running turn under its owner so it can be cancelled on its own.
+struct TurnGuard<'a> {
+    turns: &'a Mutex<HashMap<u64, Arc<Turn>>>,
+    owner: u64,
+    turn: Arc<Turn>,
+}
+
+impl<'a> TurnGuard<'a> {
+    fn new(turns: &'a Mutex<HashMap<u64, Arc<Turn>>>, owner: u64, direct_llm: bool) -> Self {
+        let turn = Arc::new(Turn {
+            direct_llm,
+            ..Turn::default()
+        });
+        if let Ok(mut turns) = turns.lock() {
+            turns.insert(owner, turn.clone());
+        }
+        Self { turns, owner, turn }
+    }
+}
+
+impl Drop for TurnGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut turns) = self.turns.lock() {
+            if turns
+                .get(&self.owner)
+                .is_some_and(|turn| Arc::ptr_eq(turn, &self.turn))
+            {
+                turns.remove(&self.owner);
+            }
+        }
+    }
+}
+
+struct SpeakingGuard<'a>(&'a AtomicBool);
+
+impl<'a> SpeakingGuard<'a> {
+    fn new(speaking: &'a AtomicBool) -> Self {
+        speaking.store(true, Ordering::Relaxed);
+        Self(speaking)
+    }
+}
+
+impl Drop for SpeakingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+// Keeps the recording owned by a turn until the turn ends, so no other client starts a second one.
+struct OwnerGuard<'a> {
+    pipeline: &'a Pipeline,
+    owner: u64,
+    direct_llm: bool,
+}
+
+impl<'a> OwnerGuard<'a> {
+    fn new(pipeline: &'a Pipeline, owner: u64) -> Result<Self> {
+        let mut claim = pipeline
+            .recording
+            .lock()
+            .map_err(|_| Error::Lock("recording".into()))?;
+        if claim.owner.is_some_and(|current| current != owner) {
+            return Err(Error::RecordingInUse);
+        }
+        claim.owner = Some(owner);
+        let direct_llm = std::mem::take(&mut claim.direct_llm);
+        drop(claim);
+        Ok(Self {
+            pipeline,
+            owner,
+            direct_llm,
+        })
+    }
+}
+
+impl Drop for OwnerGuard<'_> {
+    fn drop(&mut self) {
+        self.pipeline.release_recording(self.owner);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PipelineOptions {
+    pub vad_barge_in: bool,
+    pub wake_phrases: Vec<String>,
+    pub vocabulary: Vocabulary,
+    pub streaming_transcription: bool,
+}
+
+pub struct Pipeline {
+    recorder: Arc<dyn RecordingService>,
+    transcriber: Arc<dyn TranscriptionService>,
+    parser: Arc<dyn ParsingService>,
+    runtime: Arc<dyn RuntimeService>,
+    synthesizer: Arc<dyn SynthesizerService>,
+    recording: Mutex<RecordingClaim>,
+    vad_barge_in: bool,
+    wake_phrases: Vec<String>,
+    vocabulary: Vocabulary,
+    streaming_transcription: bool,
+    live_transcription: Mutex<Option<JoinHandle<Result<Transcript>>>>,
+    partial_transcripts: broadcast::Sender<String>,
+    turns: Mutex<HashMap<u64, Arc<Turn>>>,
+}
+
+impl Pipeline {
+    pub fn new(
+        recorder: Arc<dyn RecordingService>,
+        transcriber: Arc<dyn TranscriptionService>,
+        parser: Arc<dyn ParsingService>,
+        runtime: Arc<dyn RuntimeService>,
+        synthesizer: Arc<dyn SynthesizerService>,
+        options: PipelineOptions,
+    ) -> Self {
+        let PipelineOptions {
+            vad_barge_in,
+            wake_phrases,
+            vocabulary,
+            streaming_transcription,
+        } = options;
+        let (partial_transcripts, _) = broadcast::channel(PARTIAL_CHANNEL_CAPACITY);
+        Self {
+            recorder,
+            transcriber,
+            parser,
+            runtime,
+            synthesizer,
+            recording: Mutex::new(RecordingClaim::default()),
+            vad_barge_in,
+            wake_phrases,
+            vocabulary,
+            streaming_transcription,
+            live_transcription: Mutex::new(None),
+            partial_transcripts,
+            turns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn recorder(&self) -> &Arc<dyn RecordingService> {
+        &self.recorder
+    }
+
+    pub async fn start_recording(&self, owner: u64) -> Result<()> {
+        self.recorder.start().await?;
+        if let Ok(mut claim) = self.recording.lock() {
+            *claim = RecordingClaim {
+                owner: Some(owner),
+                direct_llm: false,
+            };
+        }
+        self.start_live_transcription();
+        Ok(())
+    }
+
+    pub fn subscribe_partial_transcripts(&self) -> broadcast::Receiver<String> {
+        self.partial_transcripts.subscribe()
+    }
+
+    fn start_live_transcription(&self) {
+        if !self.streaming_transcription {
+            return;
+        }
+        let Ok(mut live) = self.live_transcription.lock() else {
+            return;
+        };
+        if live.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let Some(audio) = self.recorder.audio_stream() else {
+            *live = None;
+            return;
+        };
+
+        let transcriber = self.transcriber.clone();
+        let runtime = self.runtime.clone();
+        let vocabulary = self.vocabulary.clone();
+        let partial_transcripts = self.partial_transcripts.clone();
+        *live = Some(tokio::spawn(async move {
+            let vocabulary = Self::collect_vocabulary(runtime.as_ref(), vocabulary).await;
+            let mut updates = transcriber.transcribe_stream(audio, &vocabulary).await?;
+            let mut committed = Transcript::default();
+            let mut pending = Transcript::default();
+            while let Some(hypothesis) = updates.next().await {
+                let hypothesis = hypothesis?;
+                if hypothesis.is_final {
+                    committed.append(hypothesis.transcript);
+                    pending = Transcript::default();
+                } else {
+                    pending = hypothesis.transcript;
+                }
+                let mut partial = committed.text.clone();
+                Self::append_text(&mut partial, &pending.text);
+                let _ = partial_transcripts.send(partial);
+            }
+            committed.append(pending);
+            Ok(committed)
+        }));
+    }
+
+    fn abandon_live_transcription(&self) {
+        let live = self
+            .live_transcription
+            .lock()
+            .ok()
+            .and_then(|mut live| live.take());
+        if let Some(live) = live {
+            live.abort();
+        }
+    }
+
+    async fn collect_vocabulary(
+        runtime: &dyn RuntimeService,
+        mut vocabulary: Vocabulary,
+    ) -> Vocabulary {
+        match runtime.vocabulary().await {
+            Ok(terms) => vocabulary.extend(terms),
+            Err(e) => warn!("Failed to collect the runtime vocabulary: {e}"),
+        }
+        vocabulary
+    }
+
+    fn append_text(text: &mut String, segment: &str) {
+        let segment = segment.trim();
+        if segment.is_empty() {
+            return;
+        }
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(segment);
+    }
+
+    async fn transcribe(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Transcript> {
+        let live = self
+            .live_transcription
+            .lock()
+            .ok()
+            .and_then(|mut live| live.take());
+        let Some(live) = live else {
+            return self.transcriber.transcribe(audio, vocabulary).await;
+        };
+        match live.await {
+            Ok(Ok(transcript)) if !transcript.text.is_empty() => Ok(transcript),
+            Ok(Ok(_)) => self.transcriber.transcribe(audio, vocabulary).await,
+            Ok(Err(e)) => {
+                warn!("Streaming transcription failed, transcribing the recording: {e}");
+                self.transcriber.transcribe(audio, vocabulary).await
+            }
+            Err(e) => {
+                warn!("Streaming transcription task failed, transcribing the recording: {e}");
+                self.transcriber.transcribe(audio, vocabulary).await
+            }
+        }
+    }
+
+    pub fn owns_recording(&self, owner: u64) -> bool {
+        self.recording
+            .lock()
+            .is_ok_and(|claim| claim.owner == Some(owner))
+    }
+
+    pub fn recording_visible_to(&self, owner: u64) -> bool {
+        self.recording
+            .lock()
+            .is_ok_and(|claim| claim.owner.is_none_or(|claimed| claimed == owner))
+    }
+
+    pub fn claim_recording(&self, owner: u64, force: bool) -> bool {
+        let Ok(mut claim) = self.recording.lock() else {
+            return false;
+        };
+        if force || claim.owner.is_none() {
+            claim.owner = Some(owner);
+        }
+        claim.owner == Some(owner)
+    }
+
+    pub fn release_recording(&self, owner: u64) -> bool {
+        let Ok(mut claim) = self.recording.lock() else {
+            return false;
+        };
+        if claim.owner == Some(owner) {
+            claim.owner = None;
+            return true;
+        }
+        false
+    }
+
+    pub fn cancel_turn(&self, owner: u64) -> bool {
+        let Some(turn) = self
+            .turns
+            .lock()
+            .ok()
+            .and_then(|turns| turns.get(&owner).cloned())
+        else {
+            return false;
+        };
+        turn.cancel.notify_one();
+        true
+    }
+
+    fn cancel_speaking_turn(&self) -> bool {
+        let Some(owner) = self.recording.lock().ok().and_then(|claim| claim.owner) else {
+            return false;
+        };
+        let Some(turn) = self
+            .turns
+            .lock()
+            .ok()
+            .and_then(|turns| turns.get(&owner).cloned())
+        else {
+            return false;
+        };
+        if !turn.speaking.load(Ordering::Relaxed) {
+            return false;
+        }
+        turn.cancel.notify_one();
+        true
+    }
+
+    /*
+     * Handles every recorder event once, whichever clients are connected.
+     * A wake word selects how the recording is answered, and a wake word or speech interrupts
+     * the reply being spoken to the owner of the recording.
+     */
+    pub async fn watch_recording_events(&self) -> Result<()> {
+        let mut events = self.recorder.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    self.on_recording_event(&event);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Pipeline missed {skipped} recording events");
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+
+    fn on_recording_event(&self, event: &RecordingEvent) -> bool {
+        let interrupt = match event {
+            RecordingEvent::WakeWordDetected(action) => {
+                if *action != WakeWordAction::Stop {
+                    if let Ok(mut claim) = self.recording.lock() {
+                        claim.direct_llm = *action == WakeWordAction::Llm;
+                    }
+                    self.start_live_transcription();
+                }
+                true
+            }
+            RecordingEvent::SpeechStarted => self.vad_barge_in,
+            RecordingEvent::NoSpeech => {
+                self.abandon_live_transcription();
+                false
+            }
+            RecordingEvent::SpeechEnded
+            | RecordingEvent::StatusChanged(_)
+            | RecordingEvent::Error(_) => false,
+        };
+        if interrupt && self.cancel_speaking_turn() {
+            info!("Barge-in on {event:?}, cancelling the reply");
+            return true;
+        }
+        false
+    }
+
+    pub async fn run_turn(
+        &self,
+        owner: u64,
+        response_kind: &ResponseKind,
+        output: &mut dyn TurnOutput,
+    ) -> Result<()> {
+        let recording = OwnerGuard::new(self, owner)?;
+        let active = TurnGuard::new(&self.turns, owner, recording.direct_llm);
+        tokio::select! {
+            result = self.run_stages(&active.turn, response_kind, output) => result,
+            () = active.turn.cancel.notified() => {
+                info!("Turn cancelled");
+                Err(Error::TurnCancelled)
+            }
+        }
+    }
+
+    async fn run_stages(
+        &self,
+        turn: &Turn,
+        response_kind: &ResponseKind,
+        output: &mut dyn TurnOutput,
+    ) -> Result<()> {
+        let audio = self.recorder.stop().await?;
+        info!("Recording stopped");
+        let vocabulary =
+            Self::collect_vocabulary(self.runtime.as_ref(), self.vocabulary.clone()).await;
+        let mut transcript = self.transcribe(&audio, &vocabulary).await?;
+        transcript.rewrite(|text| vocabulary.correct(text));
+        transcript.rewrite(|text| Self::strip_wake_phrase(text.to_string(), &self.wake_phrases));
+        info!(
+            "Transcribed text: {:?} (confidence {:?})",
+            &transcript.text, transcript.confidence
+        );
+        output.transcript(&transcript).await?;
+        let transcription = transcript.text;
+
+        let mut action = if turn.direct_llm {
+            Action::new(
+                Intent::new(IntentKind::LlmQuery, None),
+                Vec::new(),
+                transcription,
+            )
+        } else {
+            self.parser.parse(&transcription).await?
+        };
+        action.transcript_confidence = transcript.confidence;
+        info!("Action to perform: {:?}", &action);
+        output.intent(&action).await?;
+
+        let mut output_stream = self.runtime.run(action).await?;
+        info!("Runtime finished");
+        match response_kind {
+            ResponseKind::Text => {
+                while let Some(text) = output_stream.next().await {
+                    output.response(text?).await?;
+                }
+            }
+            ResponseKind::Audio => {
+                let _speaking = SpeakingGuard::new(&turn.speaking);
+                info!("Sending audio");
+                if output.streams_sentences() {
+                    let mut segments =
+                        synthesize_sentences(self.synthesizer.clone(), output_stream);
+                    while let Some(audio_stream) = segments.next().await {
+                        output.audio(audio_stream?).await?;
+                    }
+                } else {
+                    let audio_stream = self.synthesizer.synthesize(output_stream).await?;
+                    output.audio(audio_stream).await?;
+                }
+                info!("Audio sent");
+            }
+        }
+        Ok(())
+    }
+
+    fn strip_wake_phrase(text: String, phrases: &[String]) -> String {
+        let is_separator = |c: char| !c.is_alphanumeric();
+        let trimmed = text.trim_start_matches(is_separator);
+        for phrase in phrases {
+            let Some(head) = trimmed.get(..phrase.len()) else {
+                continue;
+            };
+            let rest = &trimmed[phrase.len()..];
+            if head.eq_ignore_ascii_case(phrase) && rest.chars().next().is_none_or(is_separator) {
+                return rest.trim_start_matches(is_separator).to_string();
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::device::InputDevice;
+    use crate::model::recording::RecorderStatus;
+    use crate::model::transcription::Hypothesis;
+    use crate::server::headless::HeadlessDriver;
+    use crate::service::parsing::PatternMatchParser;
+    use crate::service::playback::PlaybackService;
+    use futures::stream;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::sync::broadcast;
+
+    struct StubRecorder {
+        events: broadcast::Sender<RecordingEvent>,
+        stops: AtomicUsize,
+    }
+
+    impl StubRecorder {
+        fn new(events: broadcast::Sender<RecordingEvent>) -> Self {
+            Self {
+                events,
+                stops: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RecordingService for StubRecorder {
+        async fn start(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<Bytes> {
+            self.stops.fetch_add(1, Ordering::Relaxed);
+            Ok(Bytes::from_static(b"audio"))
+        }
+
+        fn subscribe(&self) -> broadcast::Receiver<RecordingEvent> {
+            self.events.subscribe()
+        }
+
+        fn status(&self) -> RecorderStatus {
+            RecorderStatus::Connected
+        }
+
+        fn audio_stream(&self) -> Option<BoxStream<'static, Vec<f32>>> {
+            Some(stream::iter(vec![vec![0.0; 160], vec![0.0; 160]]).boxed())
+        }
+
+        async fn list_devices(&self) -> Result<Vec<InputDevice>> {
+            Ok(Vec::new())
+        }
+
+        async fn select_device(&self, _device_name: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    struct StubTranscriber;
+
+    #[async_trait]
+    impl TranscriptionService for StubTranscriber {
+        async fn transcribe(&self, _audio: &Bytes, _vocabulary: &Vocabulary) -> Result<Transcript> {
+            Ok(Transcript::new("Close the window", None))
+        }
+
+        async fn transcribe_stream(
+            &self,
+            audio: BoxStream<'static, Vec<f32>>,
+            _vocabulary: &Vocabulary,
+        ) -> Result<BoxStream<'static, Result<Hypothesis>>> {
+            let hypotheses = [
+                Hypothesis::new(Transcript::new("Close", None), false),
+                Hypothesis::new(
+                    Transcript::new("Close the window", Some("en".to_string()))
+                        .with_confidence(Some(0.95)),
+                    true,
+                ),
+            ];
+            Ok(audio
+                .zip(stream::iter(hypotheses))
+                .map(|(_, hypothesis)| Ok(hypothesis))
+                .boxed())
+        }
+    }
+
+    struct StubRuntime;
+
+    #[async_trait]
+    impl RuntimeService for StubRuntime {
+        async fn run(&self, _action: Action) -> Result<BoxStream<'static, Result<String>>> {
+            Ok(stream::iter(vec![
+                Ok("Window closed. ".to_string()),
+                Ok("Done.".to_string()),
+            ])
+            .boxed())
+        }
+    }
+
+    struct StubSynthesizer;
+
+    #[async_trait]
+    impl SynthesizerService for StubSynthesizer {
+        async fn synthesize(
+            &self,
+            text: BoxStream<'static, Result<String>>,
+        ) -> Result<BoxStream<'static, Result<Bytes>>> {
+            Ok(text.map(|text| text.map(Bytes::from)).boxed())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordedOutput {
+        transcripts: Vec<String>,
+        languages: Vec<Option<String>>,
+        intents: Vec<IntentKind>,
+        transcript_confidences: Vec<Option<f32>>,
+        responses: Vec<String>,
+        audio: Vec<Bytes>,
+        whole_replies: bool,
+    }
+
+    #[async_trait]
+    impl TurnOutput for RecordedOutput {
+        async fn transcript(&mut self, transcript: &Transcript) -> Result<()> {
+            self.transcripts.push(transcript.text.clone());
+            self.languages.push(transcript.language.clone());
+            Ok(())
+        }
+
+        async fn intent(&mut self, action: &Action) -> Result<()> {
+            self.intents.push(action.intent.name.clone());
+            self.transcript_confidences
+                .push(action.transcript_confidence);
+            Ok(())
+        }
+
+        async fn response(&mut self, text: String) -> Result<()> {
+            self.responses.push(text);
+            Ok(())
+        }
+
+        async fn audio(&mut self, mut audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+            let mut reply = Vec::new();
+            while let Some(chunk) = audio.next().await {
+                reply.extend_from_slice(&chunk?);
+            }
+            self.audio.push(reply.into());
+            Ok(())
+        }
+
+        fn streams_sentences(&self) -> bool {
+            !self.whole_replies
+        }
+    }
+
+    fn pipeline() -> Pipeline {
+        let (events, _) = broadcast::channel(1);
+        pipeline_with(Arc::new(StubRecorder::new(events)))
+    }
+
+    fn pipeline_with(recorder: Arc<StubRecorder>) -> Pipeline {
+        Pipeline::new(
+            recorder,
+            Arc::new(StubTranscriber),
+            Arc::new(PatternMatchParser::new()),
+            Arc::new(StubRuntime),
+            Arc::new(StubSynthesizer),
+            PipelineOptions {
+                vad_barge_in: true,
+                streaming_transcription: true,
+                ..PipelineOptions::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_run_turn() -> Result<()> {
+        let pipeline = pipeline();
+
+        let mut output = RecordedOutput::default();
+        pipeline
+            .run_turn(1, &ResponseKind::Text, &mut output)
+            .await?;
+        assert_eq!(output.transcripts, vec!["Close the window"]);
+        assert_eq!(output.responses, vec!["Window closed. ", "Done."]);
+
+        let mut output = RecordedOutput::default();
+        pipeline
+            .run_turn(1, &ResponseKind::Audio, &mut output)
+            .await?;
+        assert_eq!(output.audio, vec!["Window closed.", "Done."]);
+
+        let mut output = RecordedOutput {
+            whole_replies: true,
+            ..RecordedOutput::default()
+        };
+        pipeline
+            .run_turn(1, &ResponseKind::Audio, &mut output)
+            .await?;
+        assert_eq!(output.audio, vec!["Window closed. Done."]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_llm_wake_word_skips_parser() -> Result<()> {
+        let pipeline = pipeline();
+
+        pipeline.on_recording_event(&RecordingEvent::WakeWordDetected(WakeWordAction::Llm));
+        pipeline.on_recording_event(&RecordingEvent::WakeWordDetected(WakeWordAction::Stop));
+        let mut output = RecordedOutput::default();
+        pipeline
+            .run_turn(1, &ResponseKind::Text, &mut output)
+            .await?;
+        assert_eq!(output.intents, vec![IntentKind::LlmQuery]);
+
+        let mut output = RecordedOutput::default();
+        pipeline
+            .run_turn(1, &ResponseKind::Text, &mut output)
+            .await?;
+        assert_eq!(output.intents, vec![IntentKind::CloseWindow]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_refuses_turn_for_another_owner() -> Result<()> {
+        let (events, _) = broadcast::channel(1);
+        let recorder = Arc::new(StubRecorder::new(events));
+        let pipeline = pipeline_with(recorder.clone());
+
+        pipeline.on_recording_event(&RecordingEvent::WakeWordDetected(WakeWordAction::Llm));
+        assert!(pipeline.claim_recording(1, false));
+        let mut output = RecordedOutput::default();
+        let result = pipeline.run_turn(2, &ResponseKind::Text, &mut output).await;
+        assert!(matches!(result, Err(Error::RecordingInUse)));
+        assert_eq!(recorder.stops.load(Ordering::Relaxed), 0);
+        assert!(output.transcripts.is_empty());
+
+        pipeline
+            .run_turn(1, &ResponseKind::Text, &mut output)
+            .await?;
+        assert_eq!(output.intents, vec![IntentKind::LlmQuery]);
+        assert!(!pipeline.owns_recording(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_recording_ownership() {
+        let pipeline = pipeline();
+        assert!(pipeline.recording_visible_to(2));
+
+        assert!(pipeline.claim_recording(1, false));
+        assert!(!pipeline.claim_recording(2, false));
+        assert!(pipeline.owns_recording(1));
+        assert!(pipeline.recording_visible_to(1));
+        assert!(!pipeline.recording_visible_to(2));
+        assert!(pipeline.claim_recording(2, true));
+        assert!(!pipeline.release_recording(1));
+        assert!(pipeline.release_recording(2));
+        assert!(pipeline.claim_recording(0, false));
+    }
+
+    struct StalledOutput(Option<tokio::sync::oneshot::Sender<()>>);
+
+    #[async_trait]
+    impl TurnOutput for StalledOutput {
+        async fn transcript(&mut self, _transcript: &Transcript) -> Result<()> {
+            Ok(())
+        }
+
+        async fn intent(&mut self, _action: &Action) -> Result<()> {
+            Ok(())
+        }
+
+        async fn response(&mut self, _text: String) -> Result<()> {
+            if let Some(started) = self.0.take() {
+                let _ = started.send(());
+            }
+            futures::future::pending().await
+        }
+
+        async fn audio(&mut self, _audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+            if let Some(started) = self.0.take() {
+                let _ = started.send(());
+            }
+            futures::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_barge_in_cancels_turn() -> Result<()> {
+        let pipeline = pipeline();
+        assert!(!pipeline.cancel_turn(1));
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let mut output = StalledOutput(Some(started_tx));
+        let turn = pipeline.run_turn(1, &ResponseKind::Audio, &mut output);
+        tokio::pin!(turn);
+
+        tokio::select! {
+            _ = &mut turn => panic!("Stalled turn finished"),
+            _ = started_rx => {}
+        }
+        assert!(!pipeline.on_recording_event(&RecordingEvent::SpeechEnded));
+        assert!(pipeline.on_recording_event(&RecordingEvent::SpeechStarted));
+        assert!(matches!(turn.await, Err(Error::TurnCancelled)));
+        assert!(!pipeline.cancel_turn(1));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_cancels_only_the_owners_turn() -> Result<()> {
+        let pipeline = pipeline();
+
+        let (speaking_tx, speaking_rx) = tokio::sync::oneshot::channel();
+        let mut speaking_output = StalledOutput(Some(speaking_tx));
+        let speaking = pipeline.run_turn(1, &ResponseKind::Audio, &mut speaking_output);
+        tokio::pin!(speaking);
+        tokio::select! {
+            _ = &mut speaking => panic!("Stalled turn finished"),
+            _ = speaking_rx => {}
+        }
+
+        pipeline.start_recording(2).await?;
+        let (writing_tx, writing_rx) = tokio::sync::oneshot::channel();
+        let mut writing_output = StalledOutput(Some(writing_tx));
+        let writing = pipeline.run_turn(2, &ResponseKind::Text, &mut writing_output);
+        tokio::pin!(writing);
+        tokio::select! {
+            _ = &mut speaking => panic!("Stalled turn finished"),
+            _ = &mut writing => panic!("Stalled turn finished"),
+            _ = writing_rx => {}
+        }
+        assert!(!pipeline.cancel_turn(3));
+        assert!(!pipeline.on_recording_event(&RecordingEvent::SpeechStarted));
+
+        assert!(pipeline.cancel_turn(2));
+        assert!(matches!(writing.await, Err(Error::TurnCancelled)));
+        assert!(!pipeline.cancel_turn(2));
+
+        assert!(pipeline.claim_recording(1, false));
+        assert!(pipeline.on_recording_event(&RecordingEvent::SpeechStarted));
+        assert!(matches!(speaking.await, Err(Error::TurnCancelled)));
+        assert!(!pipeline.on_recording_event(&RecordingEvent::SpeechStarted));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_watches_recording_events() -> Result<()> {
+        let (events, _) = broadcast::channel(8);
+        let pipeline = pipeline_with(Arc::new(StubRecorder::new(events.clone())));
+        let watcher = pipeline.watch_recording_events();
+        tokio::pin!(watcher);
+        tokio::select! {
+            biased;
+            _ = &mut watcher => panic!("Watcher finished"),
+            () = tokio::task::yield_now() => {}
+        }
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let mut output = StalledOutput(Some(started_tx));
+        let turn = pipeline.run_turn(1, &ResponseKind::Audio, &mut output);
+        tokio::pin!(turn);
+        tokio::select! {
+            _ = &mut watcher => panic!("Watcher finished"),
+            _ = &mut turn => panic!("Stalled turn finished"),
+            _ = started_rx => {}
+        }
+
+        assert!(events.send(RecordingEvent::SpeechStarted).is_ok());
+        tokio::select! {
+            _ = &mut watcher => panic!("Watcher finished"),
+            result = &mut turn => assert!(matches!(result, Err(Error::TurnCancelled))),
+        }
+
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct StubPlayback {
+        plays: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl PlaybackService for StubPlayback {
+        async fn play(&self, _audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+            self.plays.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_headless_driver_waits_for_session_turn() -> Result<()> {
+        let (events, _) = broadcast::channel(8);
+        let recorder = Arc::new(StubRecorder::new(events.clone()));
+        let pipeline = Arc::new(pipeline_with(recorder.clone()));
+        let playback = Arc::new(StubPlayback::default());
+        let headless = HeadlessDriver::new(pipeline.clone(), playback.clone());
+        let headless = tokio::spawn(async move { headless.run().await });
+        while events.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let mut output = StalledOutput(Some(started_tx));
+        {
+            let turn = pipeline.run_turn(1, &ResponseKind::Audio, &mut output);
+            tokio::pin!(turn);
+            tokio::select! {
+                _ = &mut turn => panic!("Stalled turn finished"),
+                _ = started_rx => {}
+            }
+
+            assert!(events.send(RecordingEvent::SpeechEnded).is_ok());
+            tokio::select! {
+                _ = &mut turn => panic!("Stalled turn finished"),
+                () = tokio::time::sleep(std::time::Duration::from_millis(50)) => {}
+            }
+            assert!(pipeline.owns_recording(1));
+        }
+
+        assert!(!pipeline.owns_recording(1));
+        assert_eq!(recorder.stops.load(Ordering::Relaxed), 1);
+        assert_eq!(playback.plays.load(Ordering::Relaxed), 0);
+        headless.abort();
+        Ok(())
+    }
+
+    #[test]
+    fn test_strip_wake_phrase() {
+        let phrases = vec!["hey ferris".to_string(), "ferris".to_string()];
+        let strip = |text: &str| Pipeline::strip_wake_phrase(text.to_string(), &phrases);
+
+        assert_eq!(
+            strip("Hey Ferris, what's the weather?"),
+            "what's the weather?"
+        );
+        assert_eq!(strip(" Ferris. Close the window"), "Close the window");
+        assert_eq!(strip("Ferrisweel"), "Ferrisweel");
+        assert_eq!(strip("Close the window"), "Close the window");
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_streams_partial_transcripts() -> Result<()> {
+        let pipeline = pipeline();
+        let mut partials = pipeline.subscribe_partial_transcripts();
+
+        pipeline.start_recording(1).await?;
+        let mut output = RecordedOutput::default();
+        pipeline
+            .run_turn(1, &ResponseKind::Text, &mut output)
+            .await?;
+
+        assert_eq!(partials.try_recv().ok(), Some("Close".to_string()));
+        assert_eq!(
+            partials.try_recv().ok(),
+            Some("Close the window".to_string())
+        );
+        assert_eq!(output.transcripts, vec!["Close the window"]);
+        assert_eq!(output.languages, vec![Some("en".to_string())]);
+        assert_eq!(output.transcript_confidences, vec![Some(0.95)]);
+        Ok(())
+    }
+}
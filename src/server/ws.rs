@@ -1,182 +1,706 @@
-use crate::config::{enums::ResponseKind, AppConfig};
-use crate::error::Result;
+use super::pipeline::{Pipeline, TurnOutput};
+use crate::config::{
+    enums::{ResponseKind, WakeWordAction},
+    AppConfig, ConfigEntry,
+};
+use crate::error::{Error, Result};
+use crate::model::action::Action;
 use crate::model::command::Command;
-use crate::service::runtime::RuntimeService;
-use crate::service::synthesis::SynthesizerService;
-use crate::service::{
-    parsing::ParsingService, recording::RecordingService, transcription::TranscriptionService,
+use crate::model::event::Event;
+use crate::model::protocol::{
+    ClientMessage, ClientMessageKind, ServerMessage, ServerMessageKind, PROTOCOL_VERSION,
 };
-use bytes::BytesMut;
+use crate::model::recording::{RecorderStatus, RecordingEvent};
+use crate::model::transcription::Transcript;
+use crate::service::timer::alarm_service::AlarmService;
+use crate::service::timer::timer_service::TimerService;
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::stream::{BoxStream, SplitSink, SplitStream};
 use futures::{SinkExt, StreamExt};
-use log::info;
+use log::{error, info, warn};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
 use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Protocol {
+    Legacy,
+    Json,
+}
+
+struct Session {
+    id: u64,
+    sink: SplitSink<WebSocketStream<TcpStream>, Message>,
+    protocol: Protocol,
+}
+
+struct SessionInput {
+    messages: SplitStream<WebSocketStream<TcpStream>>,
+    recording_events: broadcast::Receiver<RecordingEvent>,
+    partial_transcripts: broadcast::Receiver<String>,
+    queued_lines: VecDeque<String>,
+    queued_events: VecDeque<RecordingEvent>,
+}
+
+impl SessionInput {
+    const fn new(
+        messages: SplitStream<WebSocketStream<TcpStream>>,
+        recording_events: broadcast::Receiver<RecordingEvent>,
+        partial_transcripts: broadcast::Receiver<String>,
+    ) -> Self {
+        Self {
+            messages,
+            recording_events,
+            partial_transcripts,
+            queued_lines: VecDeque::new(),
+            queued_events: VecDeque::new(),
+        }
+    }
+}
+
+fn peek_command(line: &str) -> (Option<u64>, Command) {
+    if !line.trim_start().starts_with('{') {
+        return (None, line.into());
+    }
+    match serde_json::from_str::<ClientMessage>(line) {
+        Ok(ClientMessage {
+            id,
+            kind: ClientMessageKind::Cancel,
+        }) => (id, Command::Cancel),
+        _ => (None, Command::Unknown(line.to_string())),
+    }
+}
+
+impl Session {
+    const fn new(id: u64, sink: SplitSink<WebSocketStream<TcpStream>, Message>) -> Self {
+        Self {
+            id,
+            sink,
+            protocol: Protocol::Legacy,
+        }
+    }
+
+    async fn send(&mut self, id: Option<u64>, kind: ServerMessageKind) -> Result<()> {
+        self.send_as(self.protocol, id, kind).await
+    }
+
+    async fn send_as(
+        &mut self,
+        protocol: Protocol,
+        id: Option<u64>,
+        kind: ServerMessageKind,
+    ) -> Result<()> {
+        let text = match protocol {
+            Protocol::Json => serde_json::to_string(&ServerMessage::new(id, kind))?,
+            Protocol::Legacy => match kind {
+                ServerMessageKind::Response { text }
+                | ServerMessageKind::Error { message: text }
+                | ServerMessageKind::Warning { message: text } => {
+                    format!("T{text}")
+                }
+                ServerMessageKind::Cancelled => "TReply canceled.".to_string(),
+                ServerMessageKind::Config(entry) => format!("C{entry}"),
+                ServerMessageKind::Devices { devices } => format!(
+                    "TInput devices: {}",
+                    devices
+                        .iter()
+                        .map(|device| device.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                ServerMessageKind::RecorderStatus { status } => match status {
+                    RecorderStatus::Connected => "TRecorder connected.".to_string(),
+                    RecorderStatus::Reconnecting => {
+                        "TRecorder disconnected, reconnecting.".to_string()
+                    }
+                },
+                ServerMessageKind::Event(Event::AlarmFired { description }) => {
+                    format!("TAlarm: {description}")
+                }
+                ServerMessageKind::Event(Event::AlarmMissed { description }) => {
+                    format!("TAlarm went off while the server was offline: {description}")
+                }
+                ServerMessageKind::Event(Event::TimerCompleted { description }) => {
+                    format!("TTimer completed: {description}")
+                }
+                ServerMessageKind::Event(Event::TimerMissed { description }) => {
+                    format!("TTimer expired while the server was offline: {description}")
+                }
+                _ => return Ok(()),
+            },
+        };
+        self.sink.send(Message::Text(text.into())).await?;
+        Ok(())
+    }
+
+    async fn send_response(&mut self, id: Option<u64>, text: impl Into<String>) -> Result<()> {
+        self.send(id, ServerMessageKind::Response { text: text.into() })
+            .await
+    }
+
+    async fn send_error(&mut self, id: Option<u64>, message: impl Into<String>) -> Result<()> {
+        self.send(
+            id,
+            ServerMessageKind::Error {
+                message: message.into(),
+            },
+        )
+        .await
+    }
+
+    async fn send_binary(&mut self, data: Bytes) -> Result<()> {
+        self.sink.send(Message::Binary(data)).await?;
+        Ok(())
+    }
+
+    async fn stream_audio(
+        &mut self,
+        id: Option<u64>,
+        mut audio_stream: BoxStream<'static, Result<Bytes>>,
+    ) -> Result<()> {
+        self.send(id, ServerMessageKind::AudioStart).await?;
+        while let Some(chunk) = audio_stream.next().await {
+            match chunk {
+                Ok(chunk) => self.send_binary(chunk).await?,
+                Err(e) => {
+                    self.send(id, ServerMessageKind::AudioEnd).await?;
+                    return Err(e);
+                }
+            }
+        }
+        self.send(id, ServerMessageKind::AudioEnd).await
+    }
+
+    async fn send_buffered_audio(
+        &mut self,
+        mut audio_stream: BoxStream<'static, Result<Bytes>>,
+    ) -> Result<()> {
+        let mut audio_buffer = BytesMut::new();
+        while let Some(chunk) = audio_stream.next().await {
+            audio_buffer.extend_from_slice(&chunk?);
+        }
+        self.send_binary(audio_buffer.freeze()).await
+    }
+
+    async fn decode(&mut self, line: &str) -> Result<Option<(Option<u64>, Command)>> {
+        if !line.trim_start().starts_with('{') {
+            return Ok(Some((None, line.into())));
+        }
+
+        let message: ClientMessage = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(e) => {
+                self.send_error(None, format!("Invalid message: {e}"))
+                    .await?;
+                return Ok(None);
+            }
+        };
+
+        let cmd = match message.kind {
+            ClientMessageKind::Hello { version } => {
+                self.handshake(message.id, version).await?;
+                return Ok(None);
+            }
+            _ if self.protocol != Protocol::Json => {
+                self.send_error(
+                    message.id,
+                    "Send a hello message before using the JSON protocol.",
+                )
+                .await?;
+                return Ok(None);
+            }
+            ClientMessageKind::Cancel => Command::Cancel,
+            ClientMessageKind::StartRecording => Command::StartRecording,
+            ClientMessageKind::StopRecording => Command::StopRecording,
+            ClientMessageKind::GetConfig => Command::GetConfig,
+            ClientMessageKind::SetConfig { table, key, value } => {
+                Command::SetConfig(format!("{table}.{key}={value}"))
+            }
+            ClientMessageKind::ListDevices => Command::ListDevices,
+            ClientMessageKind::SelectDevice { name } => Command::SelectDevice(name),
+            ClientMessageKind::GetRecorderStatus => Command::GetRecorderStatus,
+        };
+
+        Ok(Some((message.id, cmd)))
+    }
+
+    async fn handshake(&mut self, id: Option<u64>, version: u32) -> Result<()> {
+        if version != PROTOCOL_VERSION {
+            warn!("Client requested unsupported protocol version {version}");
+            return self
+                .send_as(
+                    Protocol::Json,
+                    id,
+                    ServerMessageKind::Error {
+                        message: format!(
+                            "Unsupported protocol version {version}, the server speaks version {PROTOCOL_VERSION}."
+                        ),
+                    },
+                )
+                .await;
+        }
+
+        self.protocol = Protocol::Json;
+        info!("Client switched to JSON protocol version {version}");
+        self.send(
+            id,
+            ServerMessageKind::Hello {
+                version: PROTOCOL_VERSION,
+            },
+        )
+        .await
+    }
+}
+
+struct SessionOutput<'a> {
+    session: &'a mut Session,
+    id: Option<u64>,
+}
+
+#[async_trait]
+impl TurnOutput for SessionOutput<'_> {
+    async fn transcript(&mut self, transcript: &Transcript) -> Result<()> {
+        self.session
+            .send(
+                self.id,
+                ServerMessageKind::Transcript {
+                    text: transcript.text.clone(),
+                    language: transcript.language.clone(),
+                    confidence: transcript.confidence,
+                    words: transcript.words.clone(),
+                    alternatives: transcript.alternatives.clone(),
+                },
+            )
+            .await
+    }
+
+    async fn intent(&mut self, action: &Action) -> Result<()> {
+        self.session
+            .send(self.id, ServerMessageKind::Intent(action.clone()))
+            .await
+    }
+
+    async fn response(&mut self, text: String) -> Result<()> {
+        info!("Sending T{:?}", text);
+        self.session.send_response(self.id, text).await
+    }
+
+    async fn audio(&mut self, audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+        match self.session.protocol {
+            Protocol::Json => self.session.stream_audio(self.id, audio).await,
+            Protocol::Legacy => self.session.send_buffered_audio(audio).await,
+        }
+    }
+
+    fn streams_sentences(&self) -> bool {
+        self.session.protocol == Protocol::Json
+    }
+}
+
 pub struct WsServer {
     listener: TcpListener,
-    recorder: Box<dyn RecordingService>,
-    transcriber: Box<dyn TranscriptionService>,
-    parser: Box<dyn ParsingService>,
-    runtime: Box<dyn RuntimeService>,
-    synthesizer: Box<dyn SynthesizerService>,
+    pipeline: Arc<Pipeline>,
     response_kind: ResponseKind,
+    events: broadcast::Sender<Event>,
+    timer_service: Arc<dyn TimerService>,
+    alarm_service: Arc<dyn AlarmService>,
+    next_session_id: AtomicU64,
 }
 
 impl WsServer {
     pub async fn new(
         addr: &str,
-        recorder: Box<dyn RecordingService>,
-        transcriber: Box<dyn TranscriptionService>,
-        parser: Box<dyn ParsingService>,
-        runtime: Box<dyn RuntimeService>,
-        synthesizer: Box<dyn SynthesizerService>,
+        pipeline: Arc<Pipeline>,
         response_kind: ResponseKind,
+        events: broadcast::Sender<Event>,
+        timer_service: Arc<dyn TimerService>,
+        alarm_service: Arc<dyn AlarmService>,
     ) -> Result<Self> {
         let listener = TcpListener::bind(addr).await?;
         Ok(Self {
             listener,
-            recorder,
-            transcriber,
-            parser,
-            runtime,
-            synthesizer,
+            pipeline,
             response_kind,
+            events,
+            timer_service,
+            alarm_service,
+            next_session_id: AtomicU64::new(1),
         })
     }
 
-    async fn send_text(
+    pub async fn listen(self: Arc<Self>) -> Result<()> {
+        loop {
+            let (stream, addr) = self.listener.accept().await?;
+            info!("Accepted connection from {addr}");
+            let server = self.clone();
+            tokio::spawn(async move {
+                let ws_stream = match accept_async(stream).await {
+                    Ok(ws_stream) => ws_stream,
+                    Err(e) => {
+                        error!("WebSocket handshake with {addr} failed: {e}");
+                        return;
+                    }
+                };
+                match server.handle_client(ws_stream).await {
+                    Ok(()) => info!("Client {addr} disconnected"),
+                    Err(e) => error!("Client {addr} error: {e}"),
+                }
+            });
+        }
+    }
+
+    async fn handle_client(&self, ws_stream: WebSocketStream<TcpStream>) -> Result<()> {
+        let id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        let (sink, messages) = ws_stream.split();
+        let mut session = Session::new(id, sink);
+        let mut input = SessionInput::new(
+            messages,
+            self.pipeline.recorder().subscribe(),
+            self.pipeline.subscribe_partial_transcripts(),
+        );
+        let result = self.run_session(&mut session, &mut input).await;
+        if self.pipeline.release_recording(session.id) {
+            let _ = self.pipeline.recorder().stop().await;
+        }
+        result
+    }
+
+    async fn run_session(&self, session: &mut Session, input: &mut SessionInput) -> Result<()> {
+        let mut events = self.events.subscribe();
+        let mut replayed_missed_events = false;
+
+        loop {
+            if let Some(event) = input.queued_events.pop_front() {
+                self.handle_recording_event(session, input, event).await?;
+                continue;
+            }
+            if let Some(line) = input.queued_lines.pop_front() {
+                self.handle_text(session, input, &line).await?;
+                continue;
+            }
+
+            tokio::select! {
+                msg = input.messages.next() => {
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    if let Message::Text(line) = msg? {
+                        self.handle_text(session, input, line.as_str()).await?;
+                        if !replayed_missed_events {
+                            replayed_missed_events = true;
+                            self.replay_missed_events(session).await?;
+                        }
+                    }
+                }
+                event = input.recording_events.recv() => match event {
+                    Ok(event) => self.handle_recording_event(session, input, event).await?,
+                    Err(e) => warn!("Failed to receive recording event: {e}"),
+                },
+                partial = input.partial_transcripts.recv() => match partial {
+                    Ok(text) if self.pipeline.recording_visible_to(session.id) => {
+                        session.send(None, ServerMessageKind::PartialTranscript { text }).await?;
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to receive partial transcript: {e}"),
+                },
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        info!("Pushing event to client: {:?}", &event);
+                        session.send(None, ServerMessageKind::Event(event)).await?;
+                    }
+                    Err(e) => warn!("Failed to receive server event: {e}"),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Timers and alarms that went off while the server was down are sent to the first client,
+     * once its first message has shown which protocol it speaks.
+     */
+    async fn replay_missed_events(&self, session: &mut Session) -> Result<()> {
+        let timers = self.timer_service.missed().await?;
+        let alarms = self.alarm_service.missed().await?;
+        let events = timers
+            .into_iter()
+            .map(|timer| Event::TimerMissed {
+                description: timer.description,
+            })
+            .chain(alarms.into_iter().map(|alarm| Event::AlarmMissed {
+                description: alarm.description,
+            }));
+        for event in events {
+            session.send(None, ServerMessageKind::Event(event)).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_text(
         &self,
-        ws_stream: &mut WebSocketStream<TcpStream>,
-        text: &str,
+        session: &mut Session,
+        input: &mut SessionInput,
+        line: &str,
     ) -> Result<()> {
-        ws_stream
-            .send(Message::Text(format!("T{}", text).into()))
-            .await?;
-        Ok(())
+        let Some((id, mut cmd)) = session.decode(line).await? else {
+            return Ok(());
+        };
+        if let Command::StartRecording = cmd {
+            if self.pipeline.owns_recording(session.id) {
+                cmd = Command::StopRecording;
+            }
+        }
+        info!("Received command from client: {:?}", &cmd);
+        self.run_command(session, input, id, cmd).await
     }
 
-    async fn send_config(
+    async fn handle_recording_event(
         &self,
-        ws_stream: &mut WebSocketStream<TcpStream>,
-        entry: &str,
+        session: &mut Session,
+        input: &mut SessionInput,
+        event: RecordingEvent,
     ) -> Result<()> {
-        ws_stream
-            .send(Message::Text(format!("C{}", entry).into()))
-            .await?;
-        Ok(())
+        match event {
+            RecordingEvent::WakeWordDetected(WakeWordAction::Stop)
+            | RecordingEvent::SpeechStarted => Ok(()),
+            RecordingEvent::WakeWordDetected(_) => {
+                if self.pipeline.claim_recording(session.id, false) {
+                    info!("Session {} took over the wake word recording", session.id);
+                }
+                Ok(())
+            }
+            RecordingEvent::NoSpeech => {
+                if self.pipeline.release_recording(session.id) {
+                    session
+                        .send_response(None, "No speech heard, recording dropped.")
+                        .await?;
+                }
+                Ok(())
+            }
+            RecordingEvent::SpeechEnded => {
+                if !self.pipeline.owns_recording(session.id) {
+                    return Ok(());
+                }
+                info!(
+                    "Speech ended, finishing the turn for session {}",
+                    session.id
+                );
+                self.run_command(session, input, None, Command::StopRecording)
+                    .await
+            }
+            RecordingEvent::StatusChanged(status) => {
+                session
+                    .send(None, ServerMessageKind::RecorderStatus { status })
+                    .await
+            }
+            RecordingEvent::Error(message) => {
+                session
+                    .send_error(None, format!("Recording error: {message}"))
+                    .await
+            }
+        }
     }
 
-    pub async fn listen(&self) -> Result<()> {
-        let (stream, _addr) = self.listener.accept().await?;
-        let ws_stream = accept_async(stream).await?;
-        self.handle_client(ws_stream).await?;
+    async fn run_command(
+        &self,
+        session: &mut Session,
+        input: &mut SessionInput,
+        id: Option<u64>,
+        cmd: Command,
+    ) -> Result<()> {
+        if let Err(e) = self.handle_command(session, input, id, cmd).await {
+            if let Error::WebSocketError(_) = e {
+                return Err(e);
+            }
+            error!("Failed to handle command: {e}");
+            session.send_error(id, e.to_string()).await?;
+        }
         Ok(())
     }
 
-    async fn handle_client(&self, mut ws_stream: WebSocketStream<TcpStream>) -> Result<()> {
-        let mut recording_active = false;
+    async fn run_turn(
+        &self,
+        session: &mut Session,
+        input: &mut SessionInput,
+        id: Option<u64>,
+    ) -> Result<()> {
+        let mut cancel_id = None;
+        let owner = session.id;
+        let result = {
+            let mut output = SessionOutput {
+                session: &mut *session,
+                id,
+            };
+            let turn = self
+                .pipeline
+                .run_turn(owner, &self.response_kind, &mut output);
+            tokio::pin!(turn);
 
-        while let Some(msg) = ws_stream.next().await {
-            let msg = msg?;
-            if let Message::Text(line) = msg {
-                let mut cmd: Command = line.as_str().into();
-                if let Command::StartRecording = cmd {
-                    if recording_active {
-                        cmd = Command::StopRecording;
-                    }
-                }
-                info!("Received command from client: {:?}", &cmd);
-                match cmd {
-                    Command::StartRecording => {
-                        self.recorder.start().await?;
-                        recording_active = true;
-                    }
-                    Command::StopRecording => {
-                        let audio = self.recorder.stop().await?;
-                        recording_active = false;
-                        info!("Recording stopped");
-                        let transcription = self.transcriber.transcribe(&audio).await?;
-                        info!("Transcribed text: {:?}", &transcription);
-                        let action = self.parser.parse(&transcription).await?;
-                        info!("Action to perform: {:?}", &action);
-                        let mut output_stream = self.runtime.run(action).await?;
-                        info!("Runtime finished");
-                        match &self.response_kind {
-                            ResponseKind::Text => {
-                                while let Some(text) = output_stream.next().await {
-                                    let text = text?;
-                                    info!("Sending T{:?}", text);
-                                    self.send_text(&mut ws_stream, &format!("{}", text)).await?;
-                                }
-                            }
-                            ResponseKind::Audio => {
-                                let mut audio_stream =
-                                    self.synthesizer.synthesize(output_stream).await?;
-                                info!("Sending audio");
-                                let mut audio_buffer = BytesMut::new();
-                                while let Some(chunk) = audio_stream.next().await {
-                                    audio_buffer.extend_from_slice(&chunk?);
-                                }
-                                ws_stream.send(Message::Binary(audio_buffer.into())).await?;
-                                info!("Audio sent");
-                            }
-                        }
-                    }
-                    Command::Cancel => {
-                        if recording_active {
-                            let _ = self.recorder.stop().await?;
-                            recording_active = false;
-                            self.send_text(&mut ws_stream, "Recording canceled.")
-                                .await?;
-                        } else {
-                            self.send_text(&mut ws_stream, "Nothing to cancel.").await?;
-                        }
-                    }
-                    Command::GetConfig => {
-                        let entries = AppConfig::get_all_config_entries().await?;
-                        for entry in entries {
-                            self.send_config(&mut ws_stream, &entry).await?;
-                        }
-                    }
-                    Command::SetConfig(config_str) => {
-                        if let Some((table_key, value)) = config_str.split_once('=') {
-                            let value = value.trim();
-                            if let Some((table, key)) = table_key.split_once('.') {
-                                let table = table.trim();
-                                let key = key.trim();
-                                let value = value.trim();
-                                match AppConfig::write_config(table, key, value).await {
-                                    Ok(_) => {
-                                        info!("Set {}.{} to {}", table, key, value);
-                                        self.send_text(&mut ws_stream, "Configuration updated.")
-                                            .await?;
-                                        self.send_config(&mut ws_stream, format!("{}.{}={}", table, key, value).as_str()).await?;
-                                    }
-                                    Err(e) => {
-                                        self.send_text(
-                                            &mut ws_stream,
-                                            &format!("Error updating configuration: {}", e),
-                                        )
-                                        .await?;
-                                    }
-                                }
+            loop {
+                tokio::select! {
+                    result = &mut turn => break result,
+                    msg = input.messages.next() => {
+                        let Some(msg) = msg else {
+                            return Ok(());
+                        };
+                        if let Message::Text(line) = msg? {
+                            if let (request_id, Command::Cancel) = peek_command(line.as_str()) {
+                                cancel_id = request_id;
+                                self.pipeline.cancel_turn(owner);
                             } else {
-                                self.send_text(
-                                    &mut ws_stream,
-                                    "Invalid format. Use table.key=value.",
-                                )
-                                .await?;
+                                input.queued_lines.push_back(line.to_string());
                             }
-                        } else {
-                            self.send_text(&mut ws_stream, "Invalid format. Use table.key=value.")
-                                .await?;
                         }
                     }
-                    Command::Unknown(command) => {
-                        self.send_text(&mut ws_stream, &format!("Unknown command: {}", command))
+                    event = input.recording_events.recv() => match event {
+                        Ok(event) => input.queued_events.push_back(event),
+                        Err(e) => warn!("Failed to receive recording event: {e}"),
+                    },
+                }
+            }
+        };
+
+        match result {
+            Err(Error::TurnCancelled) => {
+                session.send(cancel_id, ServerMessageKind::Cancelled).await
+            }
+            result => result,
+        }
+    }
+
+    async fn handle_command(
+        &self,
+        session: &mut Session,
+        input: &mut SessionInput,
+        id: Option<u64>,
+        cmd: Command,
+    ) -> Result<()> {
+        match cmd {
+            Command::StartRecording => {
+                self.pipeline.start_recording(session.id).await?;
+            }
+            Command::StopRecording => {
+                self.run_turn(session, input, id).await?;
+            }
+            Command::Cancel => {
+                if self.pipeline.release_recording(session.id) {
+                    let _ = self.pipeline.recorder().stop().await?;
+                    session.send_response(id, "Recording canceled.").await?;
+                } else if self.pipeline.cancel_turn(session.id) {
+                    session.send_response(id, "Reply canceled.").await?;
+                } else {
+                    session.send_response(id, "Nothing to cancel.").await?;
+                }
+            }
+            Command::GetConfig => {
+                let entries = AppConfig::get_all_config_entries().await?;
+                for entry in entries {
+                    session.send(id, ServerMessageKind::Config(entry)).await?;
+                }
+            }
+            Command::SetConfig(config_str) => {
+                Self::set_config(session, id, &config_str).await?;
+            }
+            Command::ListDevices => {
+                let devices = self.pipeline.recorder().list_devices().await?;
+                session
+                    .send(id, ServerMessageKind::Devices { devices })
+                    .await?;
+            }
+            Command::SelectDevice(device_name) => {
+                self.select_device(session, id, device_name.trim()).await?;
+            }
+            Command::GetRecorderStatus => {
+                let status = self.pipeline.recorder().status();
+                session
+                    .send(id, ServerMessageKind::RecorderStatus { status })
+                    .await?;
+            }
+            Command::Unknown(command) => {
+                session
+                    .send_error(id, format!("Unknown command: {command}"))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn set_config(session: &mut Session, id: Option<u64>, config_str: &str) -> Result<()> {
+        if let Some((table_key, value)) = config_str.split_once('=') {
+            let value = value.trim();
+            if let Some((table, key)) = table_key.split_once('.') {
+                let table = table.trim();
+                let key = key.trim();
+                let value = value.trim();
+                match AppConfig::write_config(table, key, value).await {
+                    Ok(()) => {
+                        info!("Set {table}.{key} to {value}");
+                        session.send_response(id, "Configuration updated.").await?;
+                        session
+                            .send(
+                                id,
+                                ServerMessageKind::Config(ConfigEntry::new(table, key, value)),
+                            )
+                            .await?;
+                    }
+                    Err(e) => {
+                        session
+                            .send_error(id, format!("Error updating configuration: {e}"))
                             .await?;
                     }
                 }
+            } else {
+                session
+                    .send_error(id, "Invalid format. Use table.key=value.")
+                    .await?;
+            }
+        } else {
+            session
+                .send_error(id, "Invalid format. Use table.key=value.")
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn select_device(
+        &self,
+        session: &mut Session,
+        id: Option<u64>,
+        device_name: &str,
+    ) -> Result<()> {
+        self.pipeline.recorder().select_device(device_name).await?;
+        session
+            .send_response(id, format!("Switched input device to {device_name}."))
+            .await?;
+        match AppConfig::write_config("recording", "device_name", device_name).await {
+            Ok(()) => {
+                session
+                    .send(
+                        id,
+                        ServerMessageKind::Config(ConfigEntry::new(
+                            "recording",
+                            "device_name",
+                            device_name,
+                        )),
+                    )
+                    .await?;
+            }
+            Err(e) => {
+                warn!("Failed to save the selected input device: {e}");
+                session
+                    .send(
+                        id,
+                        ServerMessageKind::Warning {
+                            message: format!(
+                                "Could not save the input device, it will be reset on restart: {e}"
+                            ),
+                        },
+                    )
+                    .await?;
             }
         }
         Ok(())
@@ -1,6 +1,7 @@
 pub mod geocoding;
 pub mod llm;
 pub mod parsing;
+pub mod playback;
 pub mod recording;
 pub mod runtime;
 pub mod synthesis;
@@ -1,5 +1,5 @@
 use super::ParsingService;
-use crate::model::action::{DurationValue, Entity, EntityValue, Intent, IntentKind};
+use crate::model::action::{DurationValue, Entity, EntityValue, Intent, IntentKind, TimeValue};
 use crate::{error::Result, model::action::Action};
 use async_trait::async_trait;
 use regex::Regex;
@@ -8,7 +8,7 @@ use std::collections::HashMap;
 pub struct PatternMatchParser;
 
 impl PatternMatchParser {
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self
     }
 
@@ -57,6 +57,210 @@ impl PatternMatchParser {
         closest_number
     }
 
+    fn contains_any(input: &str, patterns: &[&str]) -> bool {
+        patterns.iter().any(|pattern| input.contains(pattern))
+    }
+
+    fn extract_timer_label(input: &str) -> Option<String> {
+        const NOT_LABELS: &[&str] = &[
+            "a", "an", "all", "and", "any", "cancel", "clear", "continue", "current", "delete",
+            "each", "every", "for", "hour", "hours", "list", "long", "many", "minute", "minutes",
+            "my", "new", "of", "on", "one", "pause", "remove", "resume", "running", "second",
+            "seconds", "set", "show", "start", "stop", "that", "the", "these", "this", "those",
+            "unpause", "what", "which", "your", "two", "three", "four", "five", "six", "seven",
+            "eight", "nine", "ten",
+        ];
+
+        let before = Regex::new(r"\b([a-z]+) timers?\b").unwrap();
+        let after = Regex::new(r"\btimers? (?:for|called|named) (?:the |my )?([a-z]+)\b").unwrap();
+
+        let captures: Vec<_> = before
+            .captures_iter(input)
+            .chain(after.captures_iter(input))
+            .collect();
+
+        captures
+            .iter()
+            .filter_map(|caps| caps.get(1))
+            .map(|label| label.as_str())
+            .find(|label| !NOT_LABELS.contains(label))
+            .map(str::to_string)
+    }
+
+    fn parse_timer(x: &str, input: &str) -> Action {
+        let kind = if Self::contains_any(x, &["cancel", "stop", "delete", "remove", "clear"]) {
+            IntentKind::CancelTimer
+        } else if Self::contains_any(x, &["resume", "continue", "unpause"]) {
+            IntentKind::ResumeTimer
+        } else if x.contains("pause") {
+            IntentKind::PauseTimer
+        } else if Self::contains_any(x, &["how long", "how much time", "left", "remaining"]) {
+            IntentKind::TimerRemaining
+        } else if Self::contains_any(x, &["list", "what timers", "which timers", "show"]) {
+            IntentKind::ListTimers
+        } else {
+            IntentKind::SetTimer
+        };
+
+        let mut entities = Vec::new();
+        if let Some(label) = Self::extract_timer_label(x) {
+            entities.push(Entity::new("label", EntityValue::String(label), None));
+        }
+
+        if kind == IntentKind::SetTimer {
+            match Self::extract_duration(x) {
+                Some(duration) => entities.push(Entity::new(
+                    "duration",
+                    EntityValue::Duration(duration),
+                    None,
+                )),
+                None => {
+                    return Action::new(
+                        Intent::new(IntentKind::LlmQuery, None),
+                        Vec::new(),
+                        "Please specify a clear duration for the timer.".to_string(),
+                    )
+                }
+            }
+        } else if Regex::new(r"\b(all|every)\b").unwrap().is_match(x) {
+            entities.push(Entity::new(
+                "scope",
+                EntityValue::String("all".to_string()),
+                None,
+            ));
+        }
+
+        Action::new(Intent::new(kind, None), entities, input.to_string())
+    }
+
+    fn extract_time(input: &str) -> Option<TimeValue> {
+        if input.contains("noon") {
+            return Some(TimeValue {
+                hour: 12,
+                minute: 0,
+            });
+        }
+        if input.contains("midnight") {
+            return Some(TimeValue { hour: 0, minute: 0 });
+        }
+
+        let re = Regex::new(r"\b(at )?(\d{1,2})(?::(\d{2}))?(?: ?([ap])\.?m\b\.?| ?(o'?clock)\b)?")
+            .unwrap();
+        for caps in re.captures_iter(input) {
+            let meridiem = caps.get(4).map(|m| m.as_str());
+            if caps.get(1).is_none()
+                && caps.get(3).is_none()
+                && caps.get(4).is_none()
+                && caps.get(5).is_none()
+            {
+                continue;
+            }
+
+            let mut hour = caps[2].parse::<u8>().ok()?;
+            let minute = caps
+                .get(3)
+                .map_or(Some(0), |m| m.as_str().parse::<u8>().ok())?;
+            match meridiem {
+                Some("p") if hour < 12 => hour += 12,
+                Some("a") if hour == 12 => hour = 0,
+                _ => {}
+            }
+            if hour < 24 && minute < 60 {
+                return Some(TimeValue { hour, minute });
+            }
+        }
+        None
+    }
+
+    fn extract_days(input: &str) -> (Vec<&'static str>, bool) {
+        const DAYS: [&str; 7] = [
+            "monday",
+            "tuesday",
+            "wednesday",
+            "thursday",
+            "friday",
+            "saturday",
+            "sunday",
+        ];
+
+        let mut recurring = Regex::new(r"\b(every|each|daily)\b")
+            .unwrap()
+            .is_match(input);
+        let days: Vec<&str> = if Regex::new(r"\bweekdays?\b").unwrap().is_match(input) {
+            recurring |= input.contains("weekdays");
+            DAYS[..5].to_vec()
+        } else if Regex::new(r"\bweekends?\b").unwrap().is_match(input) {
+            recurring |= input.contains("weekends");
+            DAYS[5..].to_vec()
+        } else {
+            DAYS.iter()
+                .copied()
+                .filter(|day| input.contains(day))
+                .collect()
+        };
+        recurring |= days.iter().any(|day| input.contains(&format!("{day}s")));
+
+        (days, recurring)
+    }
+
+    fn parse_alarm(x: &str, input: &str) -> Action {
+        let kind = if Self::contains_any(
+            x,
+            &[
+                "cancel", "stop", "delete", "remove", "clear", "turn off", "disable",
+            ],
+        ) {
+            IntentKind::CancelAlarm
+        } else if Self::contains_any(x, &["list", "what alarms", "which alarms", "show"]) {
+            IntentKind::ListAlarms
+        } else {
+            IntentKind::SetAlarm
+        };
+
+        let mut entities = Vec::new();
+        match Self::extract_time(x) {
+            Some(time) => entities.push(Entity::new("time", EntityValue::Time(time), None)),
+            None if kind == IntentKind::SetAlarm => {
+                return Action::new(
+                    Intent::new(IntentKind::LlmQuery, None),
+                    Vec::new(),
+                    "Please specify a time for the alarm.".to_string(),
+                )
+            }
+            None => {}
+        }
+
+        if kind == IntentKind::SetAlarm {
+            let (days, recurring) = Self::extract_days(x);
+            entities.extend(
+                days.into_iter()
+                    .map(|day| Entity::new("day", EntityValue::String(day.to_string()), None)),
+            );
+            if x.contains("tomorrow") {
+                entities.push(Entity::new(
+                    "day",
+                    EntityValue::String("tomorrow".to_string()),
+                    None,
+                ));
+            }
+            if recurring {
+                entities.push(Entity::new(
+                    "repeat",
+                    EntityValue::String("weekly".to_string()),
+                    None,
+                ));
+            }
+        } else if Regex::new(r"\b(all|every)\b").unwrap().is_match(x) {
+            entities.push(Entity::new(
+                "scope",
+                EntityValue::String("all".to_string()),
+                None,
+            ));
+        }
+
+        Action::new(Intent::new(kind, None), entities, input.to_string())
+    }
+
     fn extract_duration(input: &str) -> Option<DurationValue> {
         let re = Regex::new(r"(\d+)\s*(seconds?|minutes?|hours?)").unwrap();
         if let Some(caps) = re.captures(input) {
@@ -90,26 +294,8 @@ impl ParsingService for PatternMatchParser {
                 Vec::new(),
                 input.to_string(),
             )),
-            x if x.contains("timer") || x.contains("alarm") => {
-                if let Some(duration) = Self::extract_duration(x) {
-                    let entities = vec![Entity::new(
-                        "duration",
-                        EntityValue::Duration(duration),
-                        None,
-                    )];
-                    Ok(Action::new(
-                        Intent::new(IntentKind::SetTimer, None),
-                        entities,
-                        input.to_string(),
-                    ))
-                } else {
-                    Ok(Action::new(
-                        Intent::new(IntentKind::LlmQuery, None),
-                        Vec::new(),
-                        "Please specify a clear duration for the timer.".to_string(),
-                    ))
-                }
-            }
+            x if x.contains("alarm") || x.contains("wake me") => Ok(Self::parse_alarm(x, input)),
+            x if x.contains("timer") => Ok(Self::parse_timer(x, input)),
             x if x.contains("switch") && (x.contains("workspace") || x.contains("desktop")) => {
                 if let Some(index) = Self::get_closest_number(x, "switch") {
                     Ok(Action::new(
@@ -279,4 +465,149 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_set_timer_with_label() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("Set a pasta timer for 10 minutes").await?;
+        assert_eq!(action.intent.name, IntentKind::SetTimer);
+        assert_eq!(action.entities[0].entity, "label");
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::String("pasta".to_string())
+        );
+        assert_eq!(action.entities[1].entity, "duration");
+        assert_eq!(
+            action.entities[1].value,
+            EntityValue::Duration(DurationValue {
+                value: 10,
+                unit: "minutes".to_string()
+            })
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_timer_remaining() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("How long is left on my pasta timer").await?;
+        assert_eq!(action.intent.name, IntentKind::TimerRemaining);
+        assert_eq!(action.entities.len(), 1);
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::String("pasta".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_timer_management() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("Cancel all timers").await?;
+        assert_eq!(action.intent.name, IntentKind::CancelTimer);
+        assert_eq!(action.entities.len(), 1);
+        assert_eq!(action.entities[0].entity, "scope");
+
+        let action = parser.parse("Pause the timer").await?;
+        assert_eq!(action.intent.name, IntentKind::PauseTimer);
+        assert!(action.entities.is_empty());
+
+        let action = parser.parse("Unpause the tea timer").await?;
+        assert_eq!(action.intent.name, IntentKind::ResumeTimer);
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::String("tea".to_string())
+        );
+
+        let action = parser.parse("What timers do I have").await?;
+        assert_eq!(action.intent.name, IntentKind::ListTimers);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_set_alarm() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("Wake me up at 7:30 a.m.").await?;
+        assert_eq!(action.intent.name, IntentKind::SetAlarm);
+        assert_eq!(action.entities.len(), 1);
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::Time(TimeValue {
+                hour: 7,
+                minute: 30
+            })
+        );
+
+        let action = parser.parse("Set an alarm for 9 pm on Saturday").await?;
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::Time(TimeValue {
+                hour: 21,
+                minute: 0
+            })
+        );
+        assert_eq!(
+            action.entities[1].value,
+            EntityValue::String("saturday".to_string())
+        );
+        assert_eq!(action.entities.len(), 2);
+
+        let action = parser.parse("Set an alarm").await?;
+        assert_eq!(action.intent.name, IntentKind::LlmQuery);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_recurring_alarm() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("Set an alarm every weekday at 8").await?;
+        assert_eq!(action.intent.name, IntentKind::SetAlarm);
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::Time(TimeValue { hour: 8, minute: 0 })
+        );
+        let days: Vec<_> = action
+            .entities
+            .iter()
+            .filter(|e| e.entity == "day")
+            .collect();
+        assert_eq!(days.len(), 5);
+        assert!(action.entities.iter().any(|e| e.entity == "repeat"));
+
+        let action = parser.parse("Alarm at 6:45 on mondays").await?;
+        assert_eq!(action.entities.len(), 3);
+        assert_eq!(action.entities[2].entity, "repeat");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_match_parser_alarm_management() -> Result<()> {
+        let parser = PatternMatchParser::new();
+
+        let action = parser.parse("Cancel all alarms").await?;
+        assert_eq!(action.intent.name, IntentKind::CancelAlarm);
+        assert_eq!(action.entities[0].entity, "scope");
+
+        let action = parser.parse("Turn off the 7 am alarm").await?;
+        assert_eq!(action.intent.name, IntentKind::CancelAlarm);
+        assert_eq!(
+            action.entities[0].value,
+            EntityValue::Time(TimeValue { hour: 7, minute: 0 })
+        );
+
+        let action = parser.parse("Which alarms do I have").await?;
+        assert_eq!(action.intent.name, IntentKind::ListAlarms);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,103 @@
+use crate::error::{Error, Result};
+use bytes::Bytes;
+use std::io::{Cursor, ErrorKind};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+pub fn decode(data: Bytes) -> Result<DecodedAudio> {
+    let source = MediaSourceStream::new(
+        Box::new(Cursor::new(data)),
+        MediaSourceStreamOptions::default(),
+    );
+    let probed = symphonia::default::get_probe().format(
+        &Hint::new(),
+        source,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+
+    let track = format
+        .default_track()
+        .ok_or_else(|| Error::AudioCodec("No audio track found".to_string()))?;
+    let track_id = track.id;
+    let mut decoder =
+        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut audio = DecodedAudio {
+        samples: Vec::new(),
+        sample_rate: track.codec_params.sample_rate.unwrap_or_default(),
+        channels: track.codec_params.channels.map_or(1, |channels| {
+            u16::try_from(channels.count()).unwrap_or(u16::MAX)
+        }),
+    };
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let packet_audio = match decoder.decode(&packet) {
+            Ok(packet_audio) => packet_audio,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let spec = *packet_audio.spec();
+        let mut buffer = SampleBuffer::<f32>::new(packet_audio.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(packet_audio);
+        audio.samples.extend_from_slice(buffer.samples());
+        audio.sample_rate = spec.rate;
+        audio.channels = u16::try_from(spec.channels.count()).unwrap_or(u16::MAX);
+    }
+
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hound::{SampleFormat, WavSpec, WavWriter};
+
+    #[test]
+    fn test_decode_wav() -> Result<()> {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 22050,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = WavWriter::new(&mut cursor, spec)?;
+            for i in 0..2205 {
+                writer.write_sample((i % 100) as i16 * 100)?;
+                writer.write_sample(0i16)?;
+            }
+            writer.finalize()?;
+        }
+
+        let audio = decode(Bytes::from(cursor.into_inner()))?;
+        assert_eq!(audio.sample_rate, 22050);
+        assert_eq!(audio.channels, 2);
+        assert_eq!(audio.samples.len(), 4410);
+        assert!((audio.samples[2] - 100.0 / 32768.0).abs() < 1e-6);
+
+        Ok(())
+    }
+}
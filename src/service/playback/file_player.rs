@@ -0,0 +1,118 @@
+use super::decoder::decode;
+use super::PlaybackService;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::stream::{BoxStream, StreamExt};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+pub struct FilePlayer {
+    path: PathBuf,
+    written: Mutex<Option<(WavSpec, Vec<f32>)>>,
+}
+
+impl FilePlayer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            written: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl PlaybackService for FilePlayer {
+    async fn play(&self, mut audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+        let mut buffer = BytesMut::new();
+        while let Some(chunk) = audio.next().await {
+            buffer.extend_from_slice(&chunk?);
+        }
+        let decoded = decode(buffer.freeze())?;
+
+        let mut written = self.written.lock().await;
+        let (spec, samples) = written.get_or_insert_with(|| {
+            (
+                WavSpec {
+                    channels: decoded.channels,
+                    sample_rate: decoded.sample_rate,
+                    bits_per_sample: 32,
+                    sample_format: SampleFormat::Float,
+                },
+                Vec::new(),
+            )
+        });
+        if spec.channels != decoded.channels || spec.sample_rate != decoded.sample_rate {
+            return Err(Error::Playback(format!(
+                "Cannot append {} Hz audio to {}",
+                decoded.sample_rate,
+                self.path.display()
+            )));
+        }
+        samples.extend_from_slice(&decoded.samples);
+
+        let mut writer = WavWriter::create(&self.path, *spec)?;
+        for &sample in samples.iter() {
+            writer.write_sample(sample)?;
+        }
+        drop(written);
+        writer.finalize()?;
+
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+    use hound::WavReader;
+    use std::io::Cursor;
+
+    fn wav(samples: &[i16]) -> Result<Bytes> {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = WavWriter::new(&mut cursor, spec)?;
+            for &sample in samples {
+                writer.write_sample(sample)?;
+            }
+            writer.finalize()?;
+        }
+        Ok(Bytes::from(cursor.into_inner()))
+    }
+
+    #[tokio::test]
+    async fn test_file_player_appends_segments() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("voice-backend-playback-{}.wav", std::process::id()));
+        let player = FilePlayer::new(&path);
+
+        let first = wav(&[0, 16384, -16384])?;
+        let (head, tail) = (first.slice(..20), first.slice(20..));
+        player
+            .play(stream::iter(vec![Ok(head), Ok(tail)]).boxed())
+            .await?;
+        player
+            .play(stream::iter(vec![Ok(wav(&[8192])?)]).boxed())
+            .await?;
+
+        let samples: Vec<f32> = WavReader::open(&path)?
+            .into_samples::<f32>()
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(samples, vec![0.0, 0.5, -0.5, 0.25]);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use super::decoder::{decode, DecodedAudio};
+use super::PlaybackService;
+use crate::audio::{downmix, resample};
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{default_host, Device, FromSample, SampleFormat, SizedSample, StreamConfig};
+use futures::stream::{BoxStream, StreamExt};
+use log::{error, info};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+pub struct LocalPlayer {
+    device_name: String,
+    stop_tx: Mutex<Option<mpsc::Sender<()>>>,
+}
+
+impl LocalPlayer {
+    pub fn new(device_name: impl Into<String>) -> Self {
+        Self {
+            device_name: device_name.into(),
+            stop_tx: Mutex::new(None),
+        }
+    }
+
+    fn find_device(device_name: &str) -> Result<Device> {
+        let host = default_host();
+        if device_name == "default" {
+            return host
+                .default_output_device()
+                .ok_or(Error::AudioOutputDeviceNotFound(device_name.to_string()));
+        }
+        host.output_devices()?
+            .find(|d| d.name().is_ok_and(|n| n == device_name))
+            .ok_or(Error::AudioOutputDeviceNotFound(device_name.to_string()))
+    }
+
+    fn convert(audio: &DecodedAudio, sample_rate: u32, channels: u16) -> Vec<f32> {
+        resample(
+            &downmix(&audio.samples, audio.channels),
+            audio.sample_rate,
+            sample_rate,
+        )
+        .into_iter()
+        .flat_map(|sample| std::iter::repeat_n(sample, usize::from(channels)))
+        .collect()
+    }
+
+    fn play_blocking(
+        device_name: &str,
+        audio: &DecodedAudio,
+        done_tx: mpsc::Sender<()>,
+        done_rx: &mpsc::Receiver<()>,
+    ) -> Result<()> {
+        let device = Self::find_device(device_name)?;
+        let supported = device.default_output_config()?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+
+        let samples = Self::convert(audio, config.sample_rate.0, config.channels);
+        let duration = Duration::from_micros(
+            samples.len() as u64 * 1_000_000
+                / u64::from(config.sample_rate.0 * u32::from(config.channels)),
+        );
+
+        let stream = match sample_format {
+            SampleFormat::I16 => Self::build_stream::<i16>(&device, &config, samples, done_tx)?,
+            SampleFormat::U16 => Self::build_stream::<u16>(&device, &config, samples, done_tx)?,
+            _ => Self::build_stream::<f32>(&device, &config, samples, done_tx)?,
+        };
+        stream.play()?;
+
+        let _ = done_rx.recv_timeout(duration + Duration::from_secs(1));
+        Ok(())
+    }
+
+    fn build_stream<T: SizedSample + FromSample<f32>>(
+        device: &Device,
+        config: &StreamConfig,
+        samples: Vec<f32>,
+        done_tx: mpsc::Sender<()>,
+    ) -> Result<cpal::Stream> {
+        let mut position = 0;
+        let stream = device.build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                for sample in data.iter_mut() {
+                    *sample = T::from_sample(samples.get(position).copied().unwrap_or_default());
+                    position += 1;
+                }
+                if position >= samples.len() {
+                    let _ = done_tx.send(());
+                }
+            },
+            |err| error!("Audio output stream error: {err}"),
+            None,
+        )?;
+        Ok(stream)
+    }
+}
+
+#[async_trait]
+impl PlaybackService for LocalPlayer {
+    async fn play(&self, mut audio: BoxStream<'static, Result<Bytes>>) -> Result<()> {
+        let mut buffer = BytesMut::new();
+        while let Some(chunk) = audio.next().await {
+            buffer.extend_from_slice(&chunk?);
+        }
+
+        let decoded = decode(buffer.freeze())?;
+        info!(
+            "Playing {} samples at {} Hz",
+            decoded.samples.len(),
+            decoded.sample_rate
+        );
+        let device_name = self.device_name.clone();
+        let (done_tx, done_rx) = mpsc::channel();
+        if let Ok(mut stop_tx) = self.stop_tx.lock() {
+            *stop_tx = Some(done_tx.clone());
+        }
+        let result = tokio::task::spawn_blocking(move || {
+            Self::play_blocking(&device_name, &decoded, done_tx, &done_rx)
+        })
+        .await
+        .map_err(|e| Error::Playback(e.to_string()))?;
+        if let Ok(mut stop_tx) = self.stop_tx.lock() {
+            *stop_tx = None;
+        }
+        result
+    }
+
+    async fn stop(&self) -> Result<()> {
+        let stop_tx = self
+            .stop_tx
+            .lock()
+            .map_err(|_| Error::Lock("stop_tx".into()))?
+            .take();
+        if let Some(stop_tx) = stop_tx {
+            let _ = stop_tx.send(());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_resamples_and_duplicates_channels() {
+        let audio = DecodedAudio {
+            samples: vec![0.0, 1.0, 0.5, 0.5],
+            sample_rate: 8000,
+            channels: 2,
+        };
+
+        let output = LocalPlayer::convert(&audio, 16000, 2);
+        assert_eq!(output.len(), 8);
+        assert_eq!(&output[..2], &[0.5, 0.5]);
+        assert_eq!(&output[4..6], &[0.5, 0.5]);
+    }
+}
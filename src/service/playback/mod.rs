@@ -0,0 +1,8 @@
+pub mod decoder;
+pub mod file_player;
+pub mod local_player;
+pub mod playback_service;
+
+pub use file_player::FilePlayer;
+pub use local_player::LocalPlayer;
+pub use playback_service::PlaybackService;
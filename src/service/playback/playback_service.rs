@@ -0,0 +1,10 @@
+use crate::error::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+
+#[async_trait]
+pub trait PlaybackService: Send + Sync {
+    async fn play(&self, audio: BoxStream<'static, Result<Bytes>>) -> Result<()>;
+    async fn stop(&self) -> Result<()>;
+}
@@ -0,0 +1,227 @@
+/*
+ * Preprocessing applied to captured audio before it is buffered.
+ * Every stage works on 16 kHz mono samples and keeps its state between calls,
+ * so a chain is created once per capture stream.
+ */
+use crate::audio::{sample_count, to_f32};
+use std::f32::consts::PI;
+use std::time::Duration;
+
+const GATE_RELEASE: Duration = Duration::from_millis(50);
+const GATE_HOLD: Duration = Duration::from_millis(150);
+const GATE_SMOOTHING: Duration = Duration::from_millis(5);
+const AGC_LEVEL_WINDOW: Duration = Duration::from_millis(300);
+const AGC_ADAPTATION: Duration = Duration::from_millis(500);
+const AGC_MIN_GAIN: f32 = 0.1;
+const AGC_MAX_GAIN: f32 = 10.0;
+const AGC_SILENCE_LEVEL: f32 = 0.001;
+
+fn smoothing_factor(time: Duration, sample_rate: u32) -> f32 {
+    1.0 - (-1.0 / (time.as_secs_f32() * to_f32(sample_rate))).exp()
+}
+
+#[derive(Clone)]
+pub struct HighPassFilter {
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl HighPassFilter {
+    pub fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff_hz);
+        let dt = 1.0 / to_f32(sample_rate);
+        Self {
+            alpha: rc / (rc + dt),
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let output = self.alpha * (self.previous_output + sample - self.previous_input);
+        self.previous_input = sample;
+        self.previous_output = output;
+        output
+    }
+}
+
+#[derive(Clone)]
+pub struct NoiseGate {
+    threshold: f32,
+    release: f32,
+    smoothing: f32,
+    hold_samples: usize,
+    envelope: f32,
+    held: usize,
+    gain: f32,
+}
+
+impl NoiseGate {
+    pub fn new(threshold: f32, sample_rate: u32) -> Self {
+        Self {
+            threshold,
+            release: smoothing_factor(GATE_RELEASE, sample_rate),
+            smoothing: smoothing_factor(GATE_SMOOTHING, sample_rate),
+            hold_samples: sample_count(GATE_HOLD, sample_rate),
+            envelope: 0.0,
+            held: usize::MAX,
+            gain: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        let level = sample.abs();
+        if level > self.envelope {
+            self.envelope = level;
+        } else {
+            self.envelope += (level - self.envelope) * self.release;
+        }
+
+        if self.envelope >= self.threshold {
+            self.held = 0;
+        } else {
+            self.held = self.held.saturating_add(1);
+        }
+
+        let target = if self.held <= self.hold_samples {
+            1.0
+        } else {
+            0.0
+        };
+        self.gain += (target - self.gain) * self.smoothing;
+        sample * self.gain
+    }
+}
+
+#[derive(Clone)]
+pub struct AutomaticGainControl {
+    target_level: f32,
+    level_smoothing: f32,
+    gain_smoothing: f32,
+    mean_square: f32,
+    gain: f32,
+}
+
+impl AutomaticGainControl {
+    pub fn new(target_level: f32, sample_rate: u32) -> Self {
+        Self {
+            target_level,
+            level_smoothing: smoothing_factor(AGC_LEVEL_WINDOW, sample_rate),
+            gain_smoothing: smoothing_factor(AGC_ADAPTATION, sample_rate),
+            mean_square: 0.0,
+            gain: 1.0,
+        }
+    }
+
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.mean_square += sample.mul_add(sample, -self.mean_square) * self.level_smoothing;
+        let level = self.mean_square.sqrt();
+        if level >= AGC_SILENCE_LEVEL {
+            let desired = (self.target_level / level).clamp(AGC_MIN_GAIN, AGC_MAX_GAIN);
+            self.gain += (desired - self.gain) * self.gain_smoothing;
+        }
+        (sample * self.gain).clamp(-1.0, 1.0)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DspChain {
+    high_pass: Option<HighPassFilter>,
+    noise_gate: Option<NoiseGate>,
+    agc: Option<AutomaticGainControl>,
+}
+
+impl DspChain {
+    pub const fn new(
+        high_pass: Option<HighPassFilter>,
+        noise_gate: Option<NoiseGate>,
+        agc: Option<AutomaticGainControl>,
+    ) -> Self {
+        Self {
+            high_pass,
+            noise_gate,
+            agc,
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            if let Some(high_pass) = &mut self.high_pass {
+                *sample = high_pass.process(*sample);
+            }
+            if let Some(noise_gate) = &mut self.noise_gate {
+                *sample = noise_gate.process(*sample);
+            }
+            if let Some(agc) = &mut self.agc {
+                *sample = agc.process(*sample);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::count_to_f32;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn tone(amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..sample_count(Duration::from_secs_f32(seconds), SAMPLE_RATE))
+            .map(|i| (2.0 * PI * 440.0 * count_to_f32(i) / to_f32(SAMPLE_RATE)).sin() * amplitude)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / count_to_f32(samples.len())).sqrt()
+    }
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut chain = DspChain::new(Some(HighPassFilter::new(80.0, SAMPLE_RATE)), None, None);
+        let mut samples: Vec<f32> = tone(0.3, 1.0).iter().map(|s| s + 0.5).collect();
+        chain.process(&mut samples);
+
+        let tail = &samples[SAMPLE_RATE as usize / 2..];
+        let mean = tail.iter().sum::<f32>() / count_to_f32(tail.len());
+        assert!(mean.abs() < 0.01);
+        assert!((rms(tail) - rms(&tone(0.3, 0.5))).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_noise_gate_silences_noise_and_passes_speech() {
+        let mut chain = DspChain::new(None, Some(NoiseGate::new(0.02, SAMPLE_RATE)), None);
+
+        let mut noise = tone(0.005, 0.5);
+        chain.process(&mut noise);
+        assert!(rms(&noise[SAMPLE_RATE as usize / 4..]) < 0.0001);
+
+        let mut speech = tone(0.3, 0.5);
+        chain.process(&mut speech);
+        assert!(rms(&speech[SAMPLE_RATE as usize / 4..]) > 0.2);
+    }
+
+    #[test]
+    fn test_agc_moves_level_towards_target() {
+        let mut chain = DspChain::new(
+            None,
+            None,
+            Some(AutomaticGainControl::new(0.1, SAMPLE_RATE)),
+        );
+        let mut quiet = tone(0.02, 3.0);
+        chain.process(&mut quiet);
+        let level = rms(&quiet[SAMPLE_RATE as usize * 2..]);
+        assert!((level - 0.1).abs() < 0.02, "quiet level {level}");
+
+        let mut chain = DspChain::new(
+            None,
+            None,
+            Some(AutomaticGainControl::new(0.1, SAMPLE_RATE)),
+        );
+        let mut loud = tone(0.8, 3.0);
+        chain.process(&mut loud);
+        let level = rms(&loud[SAMPLE_RATE as usize * 2..]);
+        assert!((level - 0.1).abs() < 0.02, "loud level {level}");
+    }
+}
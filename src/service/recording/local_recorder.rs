@@ -1,263 +1,490 @@
 /*
  * Continuously captures audio into a ring buffer.
- * When recording starts, it marks the current position in the buffer.
+ * Input is captured at the device's native format and converted to 16 kHz mono on arrival.
+ * When recording starts, it marks the current position in the buffer. After a wake word, that is where
+ * the detector says the keyword ended, less the pre-roll as slack for imprecise keyword boundaries.
  * When stopped, it extracts samples between the start and stop positions to create a WAV file.
  */
+use super::dsp::DspChain;
 use super::recording_service::RecordingService;
+use super::vad::EnergyVad;
+use super::wake_word_detector::WakeWordDetector;
+use crate::audio::{downmix, encode_wav, sample_count, to_i16, Resampler, TARGET_SAMPLE_RATE};
+use crate::config::{enums::WakeWordAction, WakeWordConfig};
 use crate::error::{Error, Result};
+use crate::model::device::{InputConfig, InputDevice};
+use crate::model::recording::{RecorderStatus, RecordingEvent};
 use async_trait::async_trait;
 use bytes::Bytes;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{default_host, BufferSize, InputCallbackInfo, SampleRate, StreamConfig};
-use hound::{SampleFormat, WavSpec, WavWriter};
-use log::info;
-use porcupine::PorcupineBuilder;
+use cpal::{
+    default_host, Device, FromSample, InputCallbackInfo, SampleFormat, SizedSample, StreamConfig,
+};
+use futures::stream::{self, BoxStream, StreamExt};
+use log::{error, info, warn};
 use std::collections::VecDeque;
-use std::io::Cursor;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::{mpsc as std_mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::{broadcast, oneshot, Mutex as AsyncMutex};
+use tokio::time::{interval, MissedTickBehavior};
 
-const SAMPLE_RATE: u32 = 16000;
-const CHANNELS: u16 = 1;
+const SAMPLE_RATE: u32 = TARGET_SAMPLE_RATE;
 const RB_CAPACITY: usize = SAMPLE_RATE as usize * 600; // 10 minute buffer
+const EVENT_CHANNEL_CAPACITY: usize = 8;
+const AUDIO_STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone)]
+pub struct RecorderOptions {
+    pub wake_word_enabled: bool,
+    pub pre_roll: Duration,
+    pub dsp: DspChain,
+    pub vad_enabled: bool,
+    pub vad_threshold: f32,
+    pub vad_hang_time: Duration,
+    pub vad_no_speech_timeout: Duration,
+}
 
-pub struct LocalRecorder {
+#[derive(Clone)]
+struct CaptureState {
     buffer: Arc<Mutex<VecDeque<f32>>>,
     total_samples_captured: Arc<AtomicUsize>,
     start_index: Arc<Mutex<Option<usize>>>,
-    error_rx: Mutex<Receiver<Error>>,
+    end_index: Arc<Mutex<Option<usize>>>,
     is_recording: Arc<AtomicBool>,
+    wake_word_enabled: Arc<AtomicBool>,
+    error_tx: Sender<Error>,
+    events: broadcast::Sender<RecordingEvent>,
 }
 
-impl LocalRecorder {
-    pub fn new(
-        device_name: impl Into<String>,
-        access_key: impl Into<String>,
-        keyword_path: impl Into<String>,
-        wake_word_enabled: bool,
-        sensitivity: f32,
-    ) -> Result<Self> {
-        let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(RB_CAPACITY)));
-        let total_samples_captured = Arc::new(AtomicUsize::new(0));
-        let start_index = Arc::new(Mutex::new(None));
-        let is_recording = Arc::new(AtomicBool::new(false));
-        let wake_word_enabled = Arc::new(AtomicBool::new(wake_word_enabled));
-
+impl CaptureState {
+    fn new(wake_word_enabled: bool) -> (Self, Receiver<Error>) {
         let (error_tx, error_rx) = mpsc::channel(1);
-
-        Self::spawn_audio_thread(
-            device_name.into(),
-            buffer.clone(),
-            total_samples_captured.clone(),
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let state = Self {
+            buffer: Arc::new(Mutex::new(VecDeque::with_capacity(RB_CAPACITY))),
+            total_samples_captured: Arc::new(AtomicUsize::new(0)),
+            start_index: Arc::new(Mutex::new(None)),
+            end_index: Arc::new(Mutex::new(None)),
+            is_recording: Arc::new(AtomicBool::new(false)),
+            wake_word_enabled: Arc::new(AtomicBool::new(wake_word_enabled)),
             error_tx,
-            is_recording.clone(),
-            start_index.clone(),
-            wake_word_enabled.clone(),
-            access_key.into(),
-            keyword_path.into(),
-            sensitivity,
-        )?;
-
-        Ok(Self {
-            buffer,
-            total_samples_captured,
-            start_index,
-            error_rx: Mutex::new(error_rx),
-            is_recording,
-        })
+            events,
+        };
+        (state, error_rx)
     }
+}
 
-    fn spawn_audio_thread(
-        device_name: String,
-        buffer: Arc<Mutex<VecDeque<f32>>>,
-        total_samples_captured: Arc<AtomicUsize>,
-        error_tx: Sender<Error>,
-        is_recording: Arc<AtomicBool>,
-        start_index: Arc<Mutex<Option<usize>>>,
-        wake_word_enabled: Arc<AtomicBool>,
-        access_key: String,
-        keyword_path: String,
-        sensitivity: f32,
-    ) -> Result<()> {
-        thread::spawn(move || {
-            if let Err(e) = Self::run_audio_capture(
-                device_name,
-                buffer,
-                total_samples_captured,
-                error_tx.clone(),
-                is_recording,
-                start_index,
-                wake_word_enabled,
-                access_key,
-                keyword_path,
-                sensitivity,
-            ) {
-                let _ = error_tx.try_send(e);
-            }
-        });
-
-        Ok(())
-    }
+#[derive(Clone)]
+struct CaptureSettings {
+    detector: Arc<Mutex<Box<dyn WakeWordDetector>>>,
+    actions: Vec<WakeWordAction>,
+    pre_roll: usize,
+    dsp: DspChain,
+    vad_enabled: bool,
+    vad_threshold: f32,
+    vad_hang_time: Duration,
+    vad_no_speech_timeout: Duration,
+}
 
-    fn run_audio_capture(
-        device_name: String,
-        buffer: Arc<Mutex<VecDeque<f32>>>,
-        total_samples_captured: Arc<AtomicUsize>,
-        error_tx: Sender<Error>,
-        is_recording: Arc<AtomicBool>,
-        start_index: Arc<Mutex<Option<usize>>>,
-        wake_word_enabled: Arc<AtomicBool>,
-        access_key: String,
-        keyword_path: String,
-        sensitivity: f32,
-    ) -> Result<()> {
-        let host = default_host();
-        let device = host
-            .input_devices()?
-            .find(|d| d.name().ok().filter(|n| n == &device_name).is_some())
-            .ok_or(Error::AudioInputDeviceNotFound(device_name.clone()))?;
-
-        let config = StreamConfig {
-            channels: CHANNELS,
-            sample_rate: SampleRate(SAMPLE_RATE),
-            buffer_size: BufferSize::Default,
-        };
+// Sent from the audio callback to the capture thread, which does the frame processing.
+enum CaptureInput {
+    Samples(Vec<f32>),
+    Shutdown,
+}
 
-        let porcupine = PorcupineBuilder::new_with_keyword_paths(access_key, &[keyword_path])
-            .sensitivities(&[sensitivity])
-            .init()?;
+struct CaptureThread {
+    device_name: String,
+    input_tx: std_mpsc::Sender<CaptureInput>,
+    handle: JoinHandle<()>,
+}
 
-        let frame_length = porcupine.frame_length() as usize;
-        let mut frame_buffer = Vec::with_capacity(frame_length);
+impl CaptureThread {
+    async fn shut_down(self) -> String {
+        let Self {
+            device_name,
+            input_tx,
+            handle,
+        } = self;
+        let _ = input_tx.send(CaptureInput::Shutdown);
+        let _ = tokio::task::spawn_blocking(move || handle.join()).await;
+        device_name
+    }
+}
 
-        let data_error_tx = error_tx.clone();
-        let stream = device.build_input_stream(
-            &config,
-            move |data: &[f32], _info: &InputCallbackInfo| {
-                Self::process_audio_data(
-                    data,
-                    &buffer,
-                    &total_samples_captured,
-                    &mut frame_buffer,
-                    frame_length,
-                    &porcupine,
-                    &wake_word_enabled,
-                    &is_recording,
-                    &start_index,
-                    &data_error_tx,
-                );
-            },
-            move |err| {
-                let _ = error_tx.try_send(err.into());
-            },
-            None,
-        )?;
+/*
+ * Runs on the capture thread, off the audio callback. Appends samples to the ring buffer, then collects
+ * them into detector frames and passes each frame to the wake word detector and the voice activity detector.
+ */
+struct FrameProcessor {
+    state: CaptureState,
+    detector: Arc<Mutex<Box<dyn WakeWordDetector>>>,
+    actions: Vec<WakeWordAction>,
+    pre_roll: usize,
+    frame_length: usize,
+    frame: Vec<i16>,
+    vad: Option<EnergyVad>,
+    was_recording: bool,
+}
 
-        stream.play()?;
+impl FrameProcessor {
+    fn new(state: CaptureState, settings: &CaptureSettings) -> Result<Self> {
+        let frame_length = settings
+            .detector
+            .lock()
+            .map_err(|_| Error::Lock("detector".into()))?
+            .frame_length();
+        Ok(Self {
+            state,
+            detector: settings.detector.clone(),
+            actions: settings.actions.clone(),
+            pre_roll: settings.pre_roll,
+            frame_length,
+            frame: Vec::with_capacity(frame_length),
+            vad: settings.vad_enabled.then(|| {
+                EnergyVad::new(
+                    settings.vad_threshold,
+                    settings.vad_hang_time,
+                    settings.vad_no_speech_timeout,
+                    SAMPLE_RATE,
+                )
+            }),
+            was_recording: false,
+        })
+    }
 
-        loop {
-            thread::park();
+    fn process(&mut self, data: &[f32]) {
+        let Some(first) = self.append_to_buffer(data) else {
+            return;
+        };
+        for (index, &sample) in data.iter().enumerate() {
+            self.frame.push(to_i16(sample));
+
+            if self.frame.len() >= self.frame_length {
+                let position = first + index + 1;
+                self.process_wake_word_frame(position);
+                self.process_vad_frame(position);
+                self.frame.clear();
+            }
         }
     }
 
-    fn process_audio_data(
-        data: &[f32],
-        buffer: &Arc<Mutex<VecDeque<f32>>>,
-        total_samples_captured: &Arc<AtomicUsize>,
-        frame_buffer: &mut Vec<i16>,
-        frame_length: usize,
-        porcupine: &porcupine::Porcupine,
-        wake_word_enabled: &Arc<AtomicBool>,
-        is_recording: &Arc<AtomicBool>,
-        start_index: &Arc<Mutex<Option<usize>>>,
-        error_tx: &Sender<Error>,
-    ) {
-        if let Ok(mut buf) = buffer.lock() {
-            for &sample in data {
-                if buf.len() == RB_CAPACITY {
-                    buf.pop_front();
-                }
-                buf.push_back(sample);
-                total_samples_captured.fetch_add(1, Ordering::Relaxed);
-
-                let sample_i16 = (sample * 32767.0).clamp(-32768.0, 32767.0) as i16;
-                frame_buffer.push(sample_i16);
-
-                if frame_buffer.len() >= frame_length {
-                    Self::process_wake_word_frame(
-                        frame_buffer,
-                        porcupine,
-                        wake_word_enabled,
-                        is_recording,
-                        total_samples_captured,
-                        start_index,
-                        error_tx,
-                    );
-                }
+    // Returns the position of the first appended sample.
+    fn append_to_buffer(&self, data: &[f32]) -> Option<usize> {
+        let mut buffer = self.state.buffer.lock().ok()?;
+        for &sample in data {
+            if buffer.len() == RB_CAPACITY {
+                buffer.pop_front();
             }
+            buffer.push_back(sample);
         }
+        let first = self
+            .state
+            .total_samples_captured
+            .fetch_add(data.len(), Ordering::Relaxed);
+        drop(buffer);
+        Some(first)
     }
 
-    fn process_wake_word_frame(
-        frame_buffer: &mut Vec<i16>,
-        porcupine: &porcupine::Porcupine,
-        wake_word_enabled: &Arc<AtomicBool>,
-        is_recording: &Arc<AtomicBool>,
-        total_samples_captured: &Arc<AtomicUsize>,
-        start_index: &Arc<Mutex<Option<usize>>>,
-        error_tx: &Sender<Error>,
-    ) {
-        match porcupine.process(frame_buffer) {
-            Ok(keyword_index) => {
-                if keyword_index >= 0 {
-                    info!("Keyword triggered");
-
-                    if wake_word_enabled.load(Ordering::Relaxed)
-                        && !is_recording.load(Ordering::Relaxed)
-                    {
-                        is_recording.store(true, Ordering::Relaxed);
-                        let current = total_samples_captured.load(Ordering::Relaxed);
-                        if let Ok(mut lock) = start_index.lock() {
-                            *lock = Some(current);
+    fn process_wake_word_frame(&self, position: usize) {
+        let state = &self.state;
+        if !state.wake_word_enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let result = self.detector.lock().map_or_else(
+            |_| Err(Error::Lock("detector".into())),
+            |mut detector| {
+                detector.process(&self.frame).map(|keyword| {
+                    keyword.map(|keyword_index| (keyword_index, detector.keyword_end_offset()))
+                })
+            },
+        );
+        match result {
+            Ok(Some((keyword_index, keyword_end_offset))) => {
+                if let Some(&action) = self.actions.get(keyword_index) {
+                    info!("Keyword {keyword_index} triggered ({action:?})");
+                    if action == WakeWordAction::Stop {
+                        let _ = state.events.send(RecordingEvent::WakeWordDetected(action));
+                    } else if !state.is_recording.load(Ordering::Relaxed) {
+                        state.is_recording.store(true, Ordering::Relaxed);
+                        let keyword_end = position.saturating_sub(keyword_end_offset);
+                        if let Ok(mut lock) = state.start_index.lock() {
+                            *lock = Some(keyword_end.saturating_sub(self.pre_roll));
                         }
+                        if let Ok(mut lock) = state.end_index.lock() {
+                            *lock = None;
+                        }
+                        let _ = state.events.send(RecordingEvent::WakeWordDetected(action));
                     }
                 }
             }
+            Ok(None) => {}
             Err(e) => {
-                let _ = error_tx.try_send(e.into());
+                LocalRecorder::report_error(e, &state.error_tx, &state.events);
             }
         }
-        frame_buffer.clear();
     }
 
-    fn check_error(&self) -> Option<Error> {
-        if let Ok(mut rx) = self.error_rx.lock() {
-            rx.try_recv().ok()
-        } else {
-            None
+    fn process_vad_frame(&mut self, position: usize) {
+        let state = &self.state;
+        let Some(vad) = &mut self.vad else {
+            return;
+        };
+
+        let recording = state.is_recording.load(Ordering::Relaxed);
+        if recording != self.was_recording {
+            vad.reset();
+        }
+        self.was_recording = recording;
+
+        if !recording {
+            if vad.detect_onset(&self.frame) {
+                let _ = state.events.send(RecordingEvent::SpeechStarted);
+            }
+            return;
+        }
+
+        if vad.process(&self.frame) {
+            let event = if vad.heard_speech() {
+                info!("Speech ended");
+                RecordingEvent::SpeechEnded
+            } else {
+                info!("No speech after the recording started");
+                RecordingEvent::NoSpeech
+            };
+            state.is_recording.store(false, Ordering::Relaxed);
+            self.was_recording = false;
+            vad.reset();
+            if let Ok(mut lock) = state.end_index.lock() {
+                *lock = Some(position);
+            }
+            let _ = state.events.send(event);
         }
     }
+}
+
+pub struct LocalRecorder {
+    state: CaptureState,
+    settings: CaptureSettings,
+    capture: AsyncMutex<Option<CaptureThread>>,
+    error_rx: Mutex<Receiver<Error>>,
+}
 
-    fn create_wav_from_samples(samples: &[f32]) -> Result<Bytes> {
-        let spec = WavSpec {
-            channels: CHANNELS,
-            sample_rate: SAMPLE_RATE,
-            bits_per_sample: 32,
-            sample_format: SampleFormat::Float,
+impl LocalRecorder {
+    pub fn new(
+        device_name: impl Into<String>,
+        detector: Box<dyn WakeWordDetector>,
+        wake_words: &[WakeWordConfig],
+        options: RecorderOptions,
+    ) -> Self {
+        let (state, error_rx) = CaptureState::new(options.wake_word_enabled);
+        let settings = CaptureSettings {
+            detector: Arc::new(Mutex::new(detector)),
+            actions: wake_words.iter().map(|w| w.action).collect(),
+            pre_roll: sample_count(options.pre_roll, SAMPLE_RATE),
+            dsp: options.dsp,
+            vad_enabled: options.vad_enabled,
+            vad_threshold: options.vad_threshold,
+            vad_hang_time: options.vad_hang_time,
+            vad_no_speech_timeout: options.vad_no_speech_timeout,
         };
 
-        let mut cursor = Cursor::new(Vec::new());
-        {
-            let mut writer = WavWriter::new(&mut cursor, spec)?;
-            for &sample in samples {
-                writer.write_sample(sample)?;
+        let (capture, _) = Self::spawn_capture(device_name.into(), state.clone(), settings.clone());
+
+        Self {
+            state,
+            settings,
+            capture: AsyncMutex::new(Some(capture)),
+            error_rx: Mutex::new(error_rx),
+        }
+    }
+
+    fn spawn_capture(
+        device_name: String,
+        state: CaptureState,
+        settings: CaptureSettings,
+    ) -> (CaptureThread, oneshot::Receiver<Result<()>>) {
+        let (input_tx, input_rx) = std_mpsc::channel();
+        let (ready_tx, ready_rx) = oneshot::channel();
+
+        let thread_device_name = device_name.clone();
+        let stream_input_tx = input_tx.clone();
+        let handle = thread::spawn(move || {
+            let opened = FrameProcessor::new(state.clone(), &settings).and_then(|frames| {
+                Self::open_stream(&thread_device_name, &settings, stream_input_tx, &state)
+                    .map(|stream| (frames, stream))
+            });
+            let (mut frames, stream) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    if let Err(Err(e)) = ready_tx.send(Err(e)) {
+                        Self::report_error(e, &state.error_tx, &state.events);
+                    }
+                    return;
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+
+            for input in input_rx {
+                match input {
+                    CaptureInput::Samples(samples) => frames.process(&samples),
+                    CaptureInput::Shutdown => break,
+                }
             }
-            writer.finalize()?;
+            drop(stream);
+            info!("Stopped capturing from {thread_device_name}");
+        });
+
+        let capture = CaptureThread {
+            device_name,
+            input_tx,
+            handle,
+        };
+        (capture, ready_rx)
+    }
+
+    fn find_device(device_name: &str) -> Result<Device> {
+        let host = default_host();
+        if device_name == "default" {
+            return host
+                .default_input_device()
+                .ok_or(Error::AudioInputDeviceNotFound(device_name.to_string()));
         }
+        host.input_devices()?
+            .find(|d| d.name().is_ok_and(|n| n == device_name))
+            .ok_or(Error::AudioInputDeviceNotFound(device_name.to_string()))
+    }
+
+    fn input_devices(active: &str) -> Result<Vec<InputDevice>> {
+        let host = default_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+        let mut devices = Vec::new();
+        for device in host.input_devices()? {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+            let configs = device
+                .supported_input_configs()
+                .map(|configs| {
+                    configs
+                        .map(|config| InputConfig {
+                            channels: config.channels(),
+                            min_sample_rate: config.min_sample_rate().0,
+                            max_sample_rate: config.max_sample_rate().0,
+                            sample_format: config.sample_format().to_string(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let is_default = default_name.as_ref() == Some(&name);
+            devices.push(InputDevice {
+                is_active: name == active || (active == "default" && is_default),
+                is_default,
+                name,
+                configs,
+            });
+        }
+        Ok(devices)
+    }
 
-        Ok(Bytes::from(cursor.into_inner()))
+    fn open_stream(
+        device_name: &str,
+        settings: &CaptureSettings,
+        input_tx: std_mpsc::Sender<CaptureInput>,
+        state: &CaptureState,
+    ) -> Result<cpal::Stream> {
+        let device = Self::find_device(device_name)?;
+
+        let supported = device.default_input_config()?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        info!(
+            "Capturing from {}: {} channels at {} Hz ({:?})",
+            device_name, config.channels, config.sample_rate.0, sample_format
+        );
+
+        let mut dsp = settings.dsp.clone();
+
+        let error_state = state.clone();
+        let channels = config.channels;
+        let mut resampler = Resampler::new(config.sample_rate.0, SAMPLE_RATE);
+        let on_data = move |data: &[f32]| {
+            let mut samples = resampler.process(&downmix(data, channels));
+            dsp.process(&mut samples);
+            let _ = input_tx.send(CaptureInput::Samples(samples));
+        };
+        let on_error = move |err: cpal::StreamError| {
+            Self::report_error(err.into(), &error_state.error_tx, &error_state.events);
+        };
+        let stream = match sample_format {
+            SampleFormat::I16 => Self::build_stream::<i16>(&device, &config, on_data, on_error)?,
+            SampleFormat::U16 => Self::build_stream::<u16>(&device, &config, on_data, on_error)?,
+            _ => Self::build_stream::<f32>(&device, &config, on_data, on_error)?,
+        };
+
+        stream.play()?;
+        Ok(stream)
+    }
+
+    fn build_stream<T>(
+        device: &Device,
+        config: &StreamConfig,
+        mut on_data: impl FnMut(&[f32]) + Send + 'static,
+        on_error: impl FnMut(cpal::StreamError) + Send + 'static,
+    ) -> Result<cpal::Stream>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let mut converted = Vec::new();
+        let stream = device.build_input_stream(
+            config,
+            move |data: &[T], _info: &InputCallbackInfo| {
+                converted.clear();
+                converted.extend(data.iter().map(|&sample| sample.to_sample::<f32>()));
+                on_data(&converted);
+            },
+            on_error,
+            None,
+        )?;
+        Ok(stream)
+    }
+
+    fn report_error(
+        error: Error,
+        error_tx: &Sender<Error>,
+        events: &broadcast::Sender<RecordingEvent>,
+    ) {
+        error!("Audio capture error: {error}");
+        let _ = events.send(RecordingEvent::Error(error.to_string()));
+        let _ = error_tx.try_send(error);
+    }
+
+    fn check_error(&self) -> Option<Error> {
+        self.error_rx
+            .lock()
+            .ok()
+            .and_then(|mut rx| rx.try_recv().ok())
+    }
+
+    fn read_since(state: &CaptureState, position: usize) -> Option<(Vec<f32>, usize, bool)> {
+        let recording = state.is_recording.load(Ordering::Relaxed);
+        let end_index = *state.end_index.lock().ok()?;
+        let buffer = state.buffer.lock().ok()?;
+        let total = state.total_samples_captured.load(Ordering::Relaxed);
+        let end = end_index.unwrap_or(total).min(total);
+        let oldest = total - buffer.len();
+        let from = position.max(oldest);
+        let ended = !recording || end_index.is_some();
+        if from >= end {
+            return Some((Vec::new(), position, ended));
+        }
+        let samples = buffer.range(from - oldest..end - oldest).copied().collect();
+        drop(buffer);
+        Some((samples, end, ended))
     }
 }
 
@@ -268,14 +495,20 @@ impl RecordingService for LocalRecorder {
             return Err(err);
         }
 
-        self.is_recording.store(true, Ordering::Relaxed);
-        let current = self.total_samples_captured.load(Ordering::Relaxed);
+        self.state.is_recording.store(true, Ordering::Relaxed);
+        let current = self.state.total_samples_captured.load(Ordering::Relaxed);
 
-        let mut lock = self
+        *self
+            .state
             .start_index
             .lock()
-            .map_err(|_| Error::Lock("start_index".into()))?;
-        *lock = Some(current);
+            .map_err(|_| Error::Lock("start_index".into()))? = Some(current);
+
+        self.state
+            .end_index
+            .lock()
+            .map_err(|_| Error::Lock("end_index".into()))?
+            .take();
 
         Ok(())
     }
@@ -285,11 +518,18 @@ impl RecordingService for LocalRecorder {
             return Err(err);
         }
 
-        self.is_recording.store(false, Ordering::Relaxed);
+        self.state.is_recording.store(false, Ordering::Relaxed);
 
-        let current = self.total_samples_captured.load(Ordering::Relaxed);
+        let current = self
+            .state
+            .end_index
+            .lock()
+            .map_err(|_| Error::Lock("end_index".into()))?
+            .take()
+            .unwrap_or_else(|| self.state.total_samples_captured.load(Ordering::Relaxed));
         let start = {
             let mut lock = self
+                .state
                 .start_index
                 .lock()
                 .map_err(|_| Error::Lock("start_index".into()))?;
@@ -298,6 +538,7 @@ impl RecordingService for LocalRecorder {
 
         let snapshot = {
             let lock = self
+                .state
                 .buffer
                 .lock()
                 .map_err(|_| Error::Lock("buffer".into()))?;
@@ -319,6 +560,138 @@ impl RecordingService for LocalRecorder {
 
         let recorded: Vec<f32> = snapshot.into_iter().skip(offset).take(count).collect();
 
-        Self::create_wav_from_samples(&recorded)
+        encode_wav(&recorded)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<RecordingEvent> {
+        self.state.events.subscribe()
+    }
+
+    fn status(&self) -> RecorderStatus {
+        RecorderStatus::Connected
+    }
+
+    fn audio_stream(&self) -> Option<BoxStream<'static, Vec<f32>>> {
+        if !self.state.is_recording.load(Ordering::Relaxed) {
+            return None;
+        }
+        let start = (*self.state.start_index.lock().ok()?)?;
+        let mut ticker = interval(AUDIO_STREAM_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let stream = stream::unfold(
+            (self.state.clone(), start, ticker),
+            |(state, position, mut ticker)| async move {
+                loop {
+                    ticker.tick().await;
+                    let (samples, next, ended) = Self::read_since(&state, position)?;
+                    if !samples.is_empty() {
+                        return Some((samples, (state, next, ticker)));
+                    }
+                    if ended {
+                        return None;
+                    }
+                }
+            },
+        );
+        Some(stream.boxed())
+    }
+
+    async fn list_devices(&self) -> Result<Vec<InputDevice>> {
+        let active = self
+            .capture
+            .lock()
+            .await
+            .as_ref()
+            .map(|capture| capture.device_name.clone())
+            .unwrap_or_default();
+        Self::input_devices(&active)
+    }
+
+    async fn select_device(&self, device_name: &str) -> Result<()> {
+        let mut capture = self.capture.lock().await;
+        let previous = match capture.take() {
+            Some(current) => Some(current.shut_down().await),
+            None => None,
+        };
+
+        let (next, ready) = Self::spawn_capture(
+            device_name.to_string(),
+            self.state.clone(),
+            self.settings.clone(),
+        );
+        let error = match ready.await {
+            Ok(Ok(())) => {
+                info!("Switched input device to {}", device_name);
+                *capture = Some(next);
+                return Ok(());
+            }
+            Ok(Err(e)) => e,
+            Err(_) => Error::AudioInputDeviceNotFound(device_name.to_string()),
+        };
+
+        if let Some(previous) = previous {
+            warn!("Falling back to input device {}", previous);
+            let (restored, _) =
+                Self::spawn_capture(previous, self.state.clone(), self.settings.clone());
+            *capture = Some(restored);
+        }
+        drop(capture);
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StubDetector {
+        keyword_end_offset: usize,
+    }
+
+    impl WakeWordDetector for StubDetector {
+        fn frame_length(&self) -> usize {
+            512
+        }
+
+        fn process(&mut self, _frame: &[i16]) -> Result<Option<usize>> {
+            Ok(Some(0))
+        }
+
+        fn keyword_end_offset(&self) -> usize {
+            self.keyword_end_offset
+        }
+    }
+
+    #[test]
+    fn test_recording_starts_after_keyword_end() -> Result<()> {
+        let (state, _error_rx) = CaptureState::new(true);
+        let mut receiver = state.events.subscribe();
+        let settings = CaptureSettings {
+            detector: Arc::new(Mutex::new(Box::new(StubDetector {
+                keyword_end_offset: 3200,
+            }))),
+            actions: vec![WakeWordAction::Turn],
+            pre_roll: 800,
+            dsp: DspChain::default(),
+            vad_enabled: false,
+            vad_threshold: 0.0,
+            vad_hang_time: Duration::ZERO,
+            vad_no_speech_timeout: Duration::ZERO,
+        };
+        let mut frames = FrameProcessor::new(state.clone(), &settings)?;
+
+        state
+            .total_samples_captured
+            .store(16000 - 512, Ordering::Relaxed);
+        frames.process(&[0.0; 512]);
+
+        assert!(state.is_recording.load(Ordering::Relaxed));
+        assert_eq!(*state.start_index.lock().unwrap(), Some(16000 - 3200 - 800));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(RecordingEvent::WakeWordDetected(WakeWordAction::Turn))
+        ));
+        Ok(())
     }
 }
@@ -1,6 +1,19 @@
+pub mod dsp;
 pub mod local_recorder;
+pub mod noop_detector;
+#[cfg(feature = "porcupine")]
+pub mod porcupine_detector;
 pub mod recording_service;
 pub mod remote_recorder;
+pub mod stream_assembler;
+pub mod template_detector;
+pub mod vad;
+pub mod wake_word_detector;
 
-pub use local_recorder::LocalRecorder;
+pub use local_recorder::{LocalRecorder, RecorderOptions};
+pub use noop_detector::NoopDetector;
+#[cfg(feature = "porcupine")]
+pub use porcupine_detector::PorcupineDetector;
 pub use recording_service::RecordingService;
+pub use template_detector::TemplateDetector;
+pub use wake_word_detector::WakeWordDetector;
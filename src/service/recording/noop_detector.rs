@@ -0,0 +1,16 @@
+use super::wake_word_detector::WakeWordDetector;
+use crate::error::Result;
+
+const FRAME_LENGTH: usize = 512;
+
+pub struct NoopDetector;
+
+impl WakeWordDetector for NoopDetector {
+    fn frame_length(&self) -> usize {
+        FRAME_LENGTH
+    }
+
+    fn process(&mut self, _frame: &[i16]) -> Result<Option<usize>> {
+        Ok(None)
+    }
+}
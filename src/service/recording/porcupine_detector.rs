@@ -0,0 +1,30 @@
+use super::wake_word_detector::WakeWordDetector;
+use crate::config::WakeWordConfig;
+use crate::error::Result;
+use porcupine::{Porcupine, PorcupineBuilder};
+
+pub struct PorcupineDetector {
+    porcupine: Porcupine,
+}
+
+impl PorcupineDetector {
+    pub fn new(access_key: impl Into<String>, wake_words: &[WakeWordConfig]) -> Result<Self> {
+        let keyword_paths: Vec<&str> = wake_words.iter().map(|w| w.path.as_str()).collect();
+        let sensitivities: Vec<f32> = wake_words.iter().map(|w| w.sensitivity).collect();
+        let porcupine = PorcupineBuilder::new_with_keyword_paths(access_key, &keyword_paths)
+            .sensitivities(&sensitivities)
+            .init()?;
+        Ok(Self { porcupine })
+    }
+}
+
+impl WakeWordDetector for PorcupineDetector {
+    fn frame_length(&self) -> usize {
+        self.porcupine.frame_length() as usize
+    }
+
+    fn process(&mut self, frame: &[i16]) -> Result<Option<usize>> {
+        let keyword_index = self.porcupine.process(frame)?;
+        Ok(usize::try_from(keyword_index).ok())
+    }
+}
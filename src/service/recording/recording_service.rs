@@ -1,9 +1,20 @@
 use crate::error::Result;
+use crate::model::device::InputDevice;
+use crate::model::recording::{RecorderStatus, RecordingEvent};
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::BoxStream;
+use tokio::sync::broadcast;
 
 #[async_trait]
-pub trait RecordingService {
+pub trait RecordingService: Send + Sync {
     async fn start(&self) -> Result<()>;
     async fn stop(&self) -> Result<Bytes>;
+    fn subscribe(&self) -> broadcast::Receiver<RecordingEvent>;
+    fn status(&self) -> RecorderStatus;
+    fn audio_stream(&self) -> Option<BoxStream<'static, Vec<f32>>> {
+        None
+    }
+    async fn list_devices(&self) -> Result<Vec<InputDevice>>;
+    async fn select_device(&self, device_name: &str) -> Result<()>;
 }
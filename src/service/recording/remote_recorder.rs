@@ -1,54 +1,438 @@
+/*
+ * Records through a satellite connected over a WebSocket.
+ * The satellite either answers a stop with one binary message containing the whole WAV,
+ * or streams PCM frames after an `audio_format` header and closes the recording with `audio_end`.
+ * Streamed frames are also passed on as live audio; a recording sent as one WAV has no live audio.
+ * The connection is watched with pings and re-established with backoff when it drops.
+ */
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::{self, BoxStream};
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::net::TcpStream;
-use tokio::sync::Mutex;
-use tokio_tungstenite::{connect_async, tungstenite::{Message, Utf8Bytes}, MaybeTlsStream, WebSocketStream};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
+use tokio::time::{interval, sleep, timeout, Duration, Instant, MissedTickBehavior};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
 use crate::{
+    audio::{encode_wav, normalize_wav},
     error::{Error, Result},
-    model::command::Command,
-    service::recording::RecordingService,
+    model::{
+        audio_stream::AudioStreamMessage,
+        command::Command,
+        device::InputDevice,
+        recording::{RecorderStatus, RecordingEvent},
+    },
+    service::recording::{stream_assembler::StreamAssembler, RecordingService},
 };
 
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type LiveAudio = std::sync::Mutex<Option<mpsc::UnboundedSender<Vec<f32>>>>;
+
+// A failed stream discards its remaining frames until `audio_end` or the next `audio_format`.
+#[derive(Default)]
+enum AudioStreamState {
+    #[default]
+    Idle,
+    Streaming(StreamAssembler),
+    Failed,
+}
+
+struct Connection {
+    url: String,
+    sink: Mutex<Option<SplitSink<Socket, Message>>>,
+    recordings: mpsc::Sender<Result<Vec<f32>>>,
+    recording: AtomicBool,
+    live_audio: LiveAudio,
+    status: watch::Sender<RecorderStatus>,
+    events: broadcast::Sender<RecordingEvent>,
+}
+
 pub struct RemoteRecorder {
-    ws_stream: Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    connection: Arc<Connection>,
+    recordings: Mutex<mpsc::Receiver<Result<Vec<f32>>>>,
 }
 
 impl RemoteRecorder {
     pub async fn new(url: &str) -> Result<Self> {
         let (ws_stream, _) = connect_async(url).await?;
+        let (sink, stream) = ws_stream.split();
+        let (events, _) = broadcast::channel(8);
+        let (recordings_tx, recordings_rx) = mpsc::channel(1);
+        let connection = Arc::new(Connection {
+            url: url.to_string(),
+            sink: Mutex::new(Some(sink)),
+            recordings: recordings_tx,
+            recording: AtomicBool::new(false),
+            live_audio: LiveAudio::default(),
+            status: watch::Sender::new(RecorderStatus::Connected),
+            events,
+        });
+        tokio::spawn(Self::maintain(connection.clone(), stream));
         Ok(Self {
-            ws_stream: Mutex::new(ws_stream),
+            connection,
+            recordings: Mutex::new(recordings_rx),
         })
     }
+
+    async fn maintain(connection: Arc<Connection>, mut stream: SplitStream<Socket>) {
+        loop {
+            Self::receive(&connection, &mut stream).await;
+            connection.sink.lock().await.take();
+            if connection.recordings.is_closed() {
+                return;
+            }
+
+            warn!("Remote recorder at {} disconnected", connection.url);
+            Self::set_status(&connection, RecorderStatus::Reconnecting);
+            Self::end_live_audio(&connection.live_audio);
+            if connection.recording.swap(false, Ordering::Relaxed) {
+                let _ = connection
+                    .recordings
+                    .try_send(Err(Error::RecorderDisconnected));
+            }
+
+            let Some(next) = Self::reconnect(&connection).await else {
+                return;
+            };
+            stream = next;
+            Self::set_status(&connection, RecorderStatus::Connected);
+        }
+    }
+
+    async fn reconnect(connection: &Connection) -> Option<SplitStream<Socket>> {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            sleep(backoff).await;
+            if connection.recordings.is_closed() {
+                return None;
+            }
+            match connect_async(&connection.url).await {
+                Ok((ws_stream, _)) => {
+                    info!("Reconnected to remote recorder at {}", connection.url);
+                    let (sink, stream) = ws_stream.split();
+                    *connection.sink.lock().await = Some(sink);
+                    return Some(stream);
+                }
+                Err(e) => {
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    warn!("Failed to reconnect to remote recorder: {e}, retrying in {backoff:?}");
+                }
+            }
+        }
+    }
+
+    fn end_live_audio(live_audio: &LiveAudio) {
+        if let Ok(mut live_audio) = live_audio.lock() {
+            live_audio.take();
+        }
+    }
+
+    fn set_status(connection: &Connection, status: RecorderStatus) {
+        connection.status.send_replace(status);
+        let _ = connection
+            .events
+            .send(RecordingEvent::StatusChanged(status));
+    }
+
+    async fn receive(connection: &Connection, stream: &mut SplitStream<Socket>) {
+        let mut audio_stream = AudioStreamState::default();
+        let mut heartbeat = interval(HEARTBEAT_INTERVAL);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_seen = Instant::now();
+
+        loop {
+            let message = tokio::select! {
+                message = stream.next() => message,
+                _ = heartbeat.tick() => {
+                    if last_seen.elapsed() > HEARTBEAT_TIMEOUT {
+                        warn!("Remote recorder stopped answering pings");
+                        return;
+                    }
+                    let mut sink = connection.sink.lock().await;
+                    let pinged = match sink.as_mut() {
+                        Some(sink) => sink.send(Message::Ping(Bytes::new())).await.is_ok(),
+                        None => false,
+                    };
+                    drop(sink);
+                    if !pinged {
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            let message = match message {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => {
+                    error!("Remote recorder connection error: {e}");
+                    return;
+                }
+                None => return,
+            };
+            last_seen = Instant::now();
+
+            let recording =
+                match Self::handle_message(message, &mut audio_stream, &connection.live_audio) {
+                    Ok(Some(samples)) => Ok(samples),
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error!("Remote recorder error: {e}");
+                        let _ = connection.events.send(RecordingEvent::Error(e.to_string()));
+                        Err(e)
+                    }
+                };
+            match connection.recordings.try_send(recording) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => warn!("Dropping a recording nobody asked for"),
+                Err(TrySendError::Closed(_)) => return,
+            }
+        }
+    }
+
+    fn handle_message(
+        message: Message,
+        audio_stream: &mut AudioStreamState,
+        live_audio: &LiveAudio,
+    ) -> Result<Option<Vec<f32>>> {
+        match (message, &mut *audio_stream) {
+            (Message::Binary(data), AudioStreamState::Streaming(assembler)) => {
+                match assembler.push(&data) {
+                    Ok(samples) if !samples.is_empty() => {
+                        if let Ok(live_audio) = live_audio.lock() {
+                            if let Some(live_audio) = live_audio.as_ref() {
+                                let _ = live_audio.send(samples.to_vec());
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        *audio_stream = AudioStreamState::Failed;
+                        Self::end_live_audio(live_audio);
+                        return Err(e);
+                    }
+                }
+                Ok(None)
+            }
+            (Message::Binary(data), AudioStreamState::Idle) => normalize_wav(&data).map(Some),
+            (Message::Text(text), _) => match serde_json::from_str::<AudioStreamMessage>(&text) {
+                Ok(AudioStreamMessage::AudioFormat(format)) => {
+                    info!("Remote recorder streaming {format:?}");
+                    match StreamAssembler::new(format) {
+                        Ok(assembler) => {
+                            *audio_stream = AudioStreamState::Streaming(assembler);
+                            Ok(None)
+                        }
+                        Err(e) => {
+                            *audio_stream = AudioStreamState::Failed;
+                            Self::end_live_audio(live_audio);
+                            Err(e)
+                        }
+                    }
+                }
+                Ok(AudioStreamMessage::AudioEnd { frames }) => {
+                    Self::end_live_audio(live_audio);
+                    match std::mem::take(audio_stream) {
+                        AudioStreamState::Streaming(assembler) => {
+                            Ok(Some(assembler.finish(frames)))
+                        }
+                        AudioStreamState::Failed => Ok(None),
+                        AudioStreamState::Idle => Err(Error::ApiError(
+                            "Audio stream ended before it started".to_string(),
+                        )),
+                    }
+                }
+                Err(_) => normalize_wav(text.as_bytes()).map(Some),
+            },
+            (Message::Binary(_), AudioStreamState::Failed)
+            | (Message::Ping(_) | Message::Pong(_), _) => Ok(None),
+            _ => Err(Error::ApiError("Unexpected message type".to_string())),
+        }
+    }
+
+    async fn send(&self, command: Command) -> Result<()> {
+        let mut sink = self.connection.sink.lock().await;
+        let sent = match sink.as_mut() {
+            Some(sink) => sink.send(Message::Text(command.into())).await,
+            None => return Err(Error::RecorderDisconnected),
+        };
+        drop(sink);
+        Ok(sent?)
+    }
 }
 
 #[async_trait]
 impl RecordingService for RemoteRecorder {
     async fn start(&self) -> Result<()> {
-        let start_message = Command::StartRecording.into();
-        let mut stream = self.ws_stream.lock().await;
-        stream.send(Message::Text(start_message)).await?;
+        let mut recordings = self.recordings.lock().await;
+        while recordings.try_recv().is_ok() {}
+        drop(recordings);
+
+        self.send(Command::StartRecording).await?;
+        self.connection.recording.store(true, Ordering::Relaxed);
         Ok(())
     }
 
     async fn stop(&self) -> Result<Bytes> {
-        let stop_message = Command::StopRecording.into();
-        let mut stream = self.ws_stream.lock().await;
-        stream.send(Message::Text(stop_message)).await?;
-
-        if let Some(msg) = stream.next().await {
-            let msg = msg?;
-            match msg {
-                Message::Binary(data) => Ok(Bytes::from(data)),
-                Message::Text(text) => Ok(Bytes::from(Utf8Bytes::from(text))),
-                _ => Err(Error::ApiError("Unexpected message type".to_string())),
-            }
-        } else {
-            Err(Error::ApiError(
-                "Failed to receive audio data".to_string(),
-            ))
+        let mut recordings = self.recordings.lock().await;
+        while recordings.try_recv().is_ok() {}
+        let sent = self.send(Command::StopRecording).await;
+        let recording = match sent {
+            Ok(()) => timeout(STOP_TIMEOUT, recordings.recv())
+                .await
+                .unwrap_or(Some(Err(Error::RecorderTimeout(STOP_TIMEOUT)))),
+            Err(e) => Some(Err(e)),
+        };
+        drop(recordings);
+        self.connection.recording.store(false, Ordering::Relaxed);
+        Self::end_live_audio(&self.connection.live_audio);
+
+        match recording {
+            Some(recording) => encode_wav(&recording?),
+            None => Err(Error::RecorderDisconnected),
+        }
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<RecordingEvent> {
+        self.connection.events.subscribe()
+    }
+
+    fn status(&self) -> RecorderStatus {
+        *self.connection.status.borrow()
+    }
+
+    fn audio_stream(&self) -> Option<BoxStream<'static, Vec<f32>>> {
+        if !self.connection.recording.load(Ordering::Relaxed) {
+            return None;
         }
+        let (live_audio, receiver) = mpsc::unbounded_channel();
+        *self.connection.live_audio.lock().ok()? = Some(live_audio);
+        let stream = stream::unfold(receiver, |mut receiver| async move {
+            let samples = receiver.recv().await?;
+            Some((samples, receiver))
+        });
+        Some(stream.boxed())
+    }
+
+    async fn list_devices(&self) -> Result<Vec<InputDevice>> {
+        Err(Error::DeviceSelectionUnsupported)
+    }
+
+    async fn select_device(&self, _device_name: &str) -> Result<()> {
+        Err(Error::DeviceSelectionUnsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::audio_stream::{AudioFormat, PcmFormat};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    fn text(message: &AudioStreamMessage) -> Result<Message> {
+        Ok(Message::Text(serde_json::to_string(message)?.into()))
+    }
+
+    fn audio_format() -> Result<Message> {
+        text(&AudioStreamMessage::AudioFormat(AudioFormat {
+            sample_rate: 16000,
+            channels: 1,
+            sample_format: PcmFormat::I16,
+        }))
+    }
+
+    fn frame(sequence: u32) -> Message {
+        let mut data = sequence.to_le_bytes().to_vec();
+        data.extend([0; 320]);
+        Message::Binary(data.into())
+    }
+
+    #[test]
+    fn test_remote_recorder_discards_frames_after_stream_error() -> Result<()> {
+        let live_audio = LiveAudio::default();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        if let Ok(mut live_audio) = live_audio.lock() {
+            *live_audio = Some(sender);
+        }
+        let mut audio_stream = AudioStreamState::default();
+        let mut handle =
+            |message| RemoteRecorder::handle_message(message, &mut audio_stream, &live_audio);
+
+        assert!(handle(audio_format()?)?.is_none());
+        assert!(handle(frame(0))?.is_none());
+        assert!(receiver.try_recv().is_ok_and(|samples| !samples.is_empty()));
+        assert!(handle(Message::Binary(vec![0; 3].into())).is_err());
+        assert!(receiver.try_recv().is_err());
+        assert!(handle(frame(1))?.is_none());
+        assert!(handle(text(&AudioStreamMessage::AudioEnd { frames: 2 })?)?.is_none());
+
+        assert!(handle(audio_format()?)?.is_none());
+        assert!(handle(frame(0))?.is_none());
+        let samples = handle(text(&AudioStreamMessage::AudioEnd { frames: 1 })?)?;
+        assert_eq!(samples.map(|samples| samples.len()), Some(160));
+        Ok(())
+    }
+
+    async fn next_status(
+        events: &mut broadcast::Receiver<RecordingEvent>,
+    ) -> Result<RecorderStatus> {
+        loop {
+            let event = timeout(Duration::from_secs(5), events.recv())
+                .await
+                .map_err(|_| Error::RecorderTimeout(Duration::from_secs(5)))?;
+            if let Ok(RecordingEvent::StatusChanged(status)) = event {
+                return Ok(status);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_recorder_reconnects_after_disconnect() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}", listener.local_addr()?);
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await?;
+            let mut dropped = accept_async(socket).await?;
+            while let Some(message) = dropped.next().await {
+                if message? == Message::Text(Command::StopRecording.into()) {
+                    break;
+                }
+            }
+            drop(dropped);
+
+            let (socket, _) = listener.accept().await?;
+            let mut reconnected = accept_async(socket).await?;
+            while reconnected.next().await.is_some() {}
+            Ok::<_, Error>(())
+        });
+
+        let recorder = RemoteRecorder::new(&url).await?;
+        let mut events = recorder.subscribe();
+        recorder.start().await?;
+        assert!(matches!(
+            recorder.stop().await,
+            Err(Error::RecorderDisconnected)
+        ));
+        assert_eq!(
+            next_status(&mut events).await?,
+            RecorderStatus::Reconnecting
+        );
+        assert_eq!(next_status(&mut events).await?, RecorderStatus::Connected);
+        assert_eq!(recorder.status(), RecorderStatus::Connected);
+
+        server.abort();
+        Ok(())
     }
 }
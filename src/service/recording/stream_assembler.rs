@@ -0,0 +1,155 @@
+/*
+ * Assembles PCM frames streamed by a remote recorder into 16 kHz mono audio.
+ * Frames carry a sequence number; missing frames are replaced with silence so the timing stays intact,
+ * and repeated frames are dropped. A gap longer than `MAX_GAP` means the stream is corrupt and is rejected.
+ * Each push returns the samples it added, so the recording can be transcribed while it streams.
+ */
+use crate::audio::{downmix, Resampler, TARGET_SAMPLE_RATE};
+use crate::error::{Error, Result};
+use crate::model::audio_stream::{AudioFormat, AudioFrame};
+use log::warn;
+use std::time::Duration;
+
+const MAX_GAP: Duration = Duration::from_secs(1);
+
+pub struct StreamAssembler {
+    format: AudioFormat,
+    resampler: Resampler,
+    samples: Vec<f32>,
+    next_sequence: u32,
+}
+
+impl StreamAssembler {
+    pub fn new(format: AudioFormat) -> Result<Self> {
+        if format.channels == 0 || format.sample_rate == 0 {
+            return Err(Error::AudioCodec(format!(
+                "Unsupported stream format: {} channels at {} Hz",
+                format.channels, format.sample_rate
+            )));
+        }
+        Ok(Self {
+            format,
+            resampler: Resampler::new(format.sample_rate, TARGET_SAMPLE_RATE),
+            samples: Vec::new(),
+            next_sequence: 0,
+        })
+    }
+
+    pub fn push(&mut self, data: &[u8]) -> Result<&[f32]> {
+        let frame = AudioFrame::decode(data, self.format.sample_format)?;
+        if frame.sequence < self.next_sequence {
+            warn!("Dropping repeated audio frame {}", frame.sequence);
+            return Ok(&[]);
+        }
+        let assembled = self.samples.len();
+
+        let missing = u64::from(frame.sequence - self.next_sequence);
+        if missing > 0 {
+            let gap = frame.samples.len() as u64 * missing;
+            let max_gap = u64::from(self.format.sample_rate)
+                * u64::from(self.format.channels)
+                * MAX_GAP.as_secs();
+            if gap > max_gap {
+                return Err(Error::AudioCodec(format!(
+                    "Audio frames {}..{} are missing, more than {:?} of audio",
+                    self.next_sequence, frame.sequence, MAX_GAP
+                )));
+            }
+            warn!(
+                "Missing audio frames {}..{}, filling with silence",
+                self.next_sequence, frame.sequence
+            );
+            let silence = vec![0.0; usize::try_from(gap).unwrap_or_default()];
+            self.append(&silence);
+        }
+        self.append(&frame.samples);
+        self.next_sequence = frame.sequence.saturating_add(1);
+        Ok(&self.samples[assembled..])
+    }
+
+    fn append(&mut self, samples: &[f32]) {
+        let mono = downmix(samples, self.format.channels);
+        self.samples.extend(self.resampler.process(&mono));
+    }
+
+    pub fn finish(mut self, frames: u32) -> Vec<f32> {
+        if frames != self.next_sequence {
+            warn!(
+                "Remote recorder sent {} audio frames, received {}",
+                frames, self.next_sequence
+            );
+        }
+        self.samples.extend(self.resampler.flush());
+        self.samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::audio_stream::PcmFormat;
+
+    fn frame(sequence: u32, samples: &[i16]) -> Vec<u8> {
+        let mut data = sequence.to_le_bytes().to_vec();
+        for sample in samples {
+            data.extend(sample.to_le_bytes());
+        }
+        data
+    }
+
+    fn format(sample_rate: u32, channels: u16) -> AudioFormat {
+        AudioFormat {
+            sample_rate,
+            channels,
+            sample_format: PcmFormat::I16,
+        }
+    }
+
+    #[test]
+    fn test_stream_assembler_downmixes_and_resamples() -> Result<()> {
+        let mut assembler = StreamAssembler::new(format(32000, 2))?;
+        for sequence in 0..10 {
+            assembler.push(&frame(sequence, &[16384; 640]))?;
+        }
+
+        let samples = assembler.finish(10);
+        assert_eq!(samples.len(), 1600);
+        assert!(samples.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_assembler_fills_gaps_and_drops_repeats() -> Result<()> {
+        let mut assembler = StreamAssembler::new(format(TARGET_SAMPLE_RATE, 1))?;
+        assembler.push(&frame(0, &[16384; 160]))?;
+        assembler.push(&frame(0, &[16384; 160]))?;
+        assembler.push(&frame(3, &[16384; 160]))?;
+
+        let samples = assembler.finish(4);
+        assert_eq!(samples.len(), 640);
+        assert!(samples[160..480].iter().all(|&s| s == 0.0));
+        assert!(samples[480..].iter().all(|&s| (s - 0.5).abs() < 1e-6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_assembler_rejects_long_gaps() -> Result<()> {
+        let mut assembler = StreamAssembler::new(format(TARGET_SAMPLE_RATE, 1))?;
+        assembler.push(&frame(0, &[16384; 160]))?;
+        assert!(assembler.push(&frame(101, &[16384; 160])).is_ok());
+        assert!(matches!(
+            assembler.push(&frame(u32::MAX, &[16384; 160])),
+            Err(Error::AudioCodec(_))
+        ));
+        assert!(matches!(
+            assembler.push(&frame(203, &[16384; 160])),
+            Err(Error::AudioCodec(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_assembler_rejects_empty_format() {
+        assert!(StreamAssembler::new(format(0, 1)).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+/*
+ * Offline wake word detection by template matching.
+ * Each wake word is a short WAV recording of the keyword. Incoming audio is turned into MFCC frames,
+ * and the most recent frames are compared against every template with subsequence DTW.
+ */
+use super::wake_word_detector::WakeWordDetector;
+use crate::audio::mfcc::{Mfcc, FRAME_LENGTH, HOP_LENGTH};
+use crate::audio::{count_to_f32, normalize_wav, TARGET_SAMPLE_RATE};
+use crate::config::WakeWordConfig;
+use crate::error::{Error, Result};
+use std::collections::VecDeque;
+
+const SILENCE_RMS: f32 = 0.01;
+const COOLDOWN_FRAMES: usize = 100;
+const MIN_THRESHOLD: f32 = 0.15;
+const THRESHOLD_RANGE: f32 = 0.2;
+
+struct Template {
+    features: Vec<Vec<f32>>,
+    threshold: f32,
+}
+
+pub struct TemplateDetector {
+    mfcc: Mfcc,
+    templates: Vec<Template>,
+    samples: VecDeque<f32>,
+    history: VecDeque<Vec<f32>>,
+    history_length: usize,
+    frames_since_voice: usize,
+    keyword_end_offset: usize,
+    cooldown: usize,
+}
+
+impl TemplateDetector {
+    pub fn new(wake_words: &[WakeWordConfig]) -> Result<Self> {
+        let recordings = wake_words
+            .iter()
+            .map(|wake_word| {
+                let samples = normalize_wav(&std::fs::read(&wake_word.path)?)?;
+                Ok((samples, wake_word.sensitivity))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_recordings(&recordings)
+    }
+
+    fn from_recordings(recordings: &[(Vec<f32>, f32)]) -> Result<Self> {
+        let mfcc = Mfcc::new(TARGET_SAMPLE_RATE);
+        let templates = recordings
+            .iter()
+            .enumerate()
+            .map(|(index, (samples, sensitivity))| {
+                let features = mfcc.compute_all(Self::trim_silence(samples));
+                if features.is_empty() {
+                    return Err(Error::AudioCodec(format!(
+                        "Wake word template {index} contains no speech"
+                    )));
+                }
+                Ok(Template {
+                    features,
+                    threshold: THRESHOLD_RANGE.mul_add(sensitivity.clamp(0.0, 1.0), MIN_THRESHOLD),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let history_length = templates
+            .iter()
+            .map(|template| template.features.len() * 2)
+            .max()
+            .unwrap_or_default();
+
+        Ok(Self {
+            mfcc,
+            templates,
+            samples: VecDeque::with_capacity(FRAME_LENGTH),
+            history: VecDeque::with_capacity(history_length),
+            history_length,
+            frames_since_voice: usize::MAX,
+            keyword_end_offset: 0,
+            cooldown: 0,
+        })
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        (samples.iter().map(|s| s * s).sum::<f32>() / count_to_f32(samples.len())).sqrt()
+    }
+
+    fn trim_silence(samples: &[f32]) -> &[f32] {
+        let voiced: Vec<usize> = samples
+            .chunks(HOP_LENGTH)
+            .enumerate()
+            .filter(|(_, chunk)| Self::rms(chunk) >= SILENCE_RMS)
+            .map(|(index, _)| index)
+            .collect();
+        match (voiced.first(), voiced.last()) {
+            (Some(&first), Some(&last)) => {
+                let end = ((last + 1) * HOP_LENGTH + FRAME_LENGTH).min(samples.len());
+                &samples[first * HOP_LENGTH..end]
+            }
+            _ => &[],
+        }
+    }
+
+    fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt()
+            * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+        if norm <= f32::EPSILON {
+            return 1.0;
+        }
+        1.0 - dot / norm
+    }
+
+    fn subsequence_dtw(template: &[Vec<f32>], history: &VecDeque<Vec<f32>>) -> f32 {
+        let columns = history.len();
+        let mut previous: Vec<(f32, usize)> = history
+            .iter()
+            .map(|frame| (Self::cosine_distance(&template[0], frame), 1))
+            .collect();
+
+        for row in &template[1..] {
+            let mut current = Vec::with_capacity(columns);
+            for (column, frame) in history.iter().enumerate() {
+                let cost = Self::cosine_distance(row, frame);
+                let best = if column == 0 {
+                    previous[0]
+                } else {
+                    [previous[column], current[column - 1], previous[column - 1]]
+                        .into_iter()
+                        .min_by(|a: &(f32, usize), b| a.0.total_cmp(&b.0))
+                        .unwrap_or(previous[column])
+                };
+                current.push((best.0 + cost, best.1 + 1));
+            }
+            previous = current;
+        }
+
+        previous
+            .last()
+            .map_or(f32::MAX, |&(cost, length)| cost / count_to_f32(length))
+    }
+}
+
+impl WakeWordDetector for TemplateDetector {
+    fn frame_length(&self) -> usize {
+        HOP_LENGTH
+    }
+
+    fn process(&mut self, frame: &[i16]) -> Result<Option<usize>> {
+        for &sample in frame {
+            if self.samples.len() == FRAME_LENGTH {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(f32::from(sample) / 32768.0);
+        }
+        if self.samples.len() < FRAME_LENGTH {
+            return Ok(None);
+        }
+
+        let window = self.samples.make_contiguous();
+        let features = self.mfcc.compute(window);
+        if Self::rms(window) >= SILENCE_RMS {
+            self.frames_since_voice = 0;
+        } else {
+            self.frames_since_voice = self.frames_since_voice.saturating_add(1);
+        }
+
+        if self.history.len() == self.history_length {
+            self.history.pop_front();
+        }
+        self.history.push_back(features);
+
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return Ok(None);
+        }
+
+        let detected = self
+            .templates
+            .iter()
+            .enumerate()
+            .filter(|(_, template)| {
+                self.frames_since_voice < template.features.len()
+                    && self.history.len() >= template.features.len() / 2
+            })
+            .map(|(index, template)| {
+                let distance = Self::subsequence_dtw(&template.features, &self.history);
+                (index, distance, template.threshold)
+            })
+            .filter(|&(_, distance, threshold)| distance <= threshold)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _, _)| index);
+
+        if detected.is_some() {
+            self.keyword_end_offset = self.frames_since_voice * HOP_LENGTH;
+            self.history.clear();
+            self.cooldown = COOLDOWN_FRAMES;
+        }
+        Ok(detected)
+    }
+
+    fn keyword_end_offset(&self) -> usize {
+        self.keyword_end_offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{to_f32, to_i16};
+    use std::f32::consts::PI;
+
+    fn tones(frequencies: &[f32]) -> Vec<f32> {
+        frequencies
+            .iter()
+            .flat_map(|&frequency| {
+                (0..3200).map(move |i| {
+                    (2.0 * PI * frequency * to_f32(i) / to_f32(TARGET_SAMPLE_RATE)).sin() * 0.5
+                })
+            })
+            .collect()
+    }
+
+    fn detections(detector: &mut TemplateDetector, samples: &[f32]) -> Result<Vec<usize>> {
+        let mut detections = Vec::new();
+        for chunk in samples.chunks(HOP_LENGTH) {
+            let frame: Vec<i16> = chunk.iter().map(|&s| to_i16(s)).collect();
+            if let Some(index) = detector.process(&frame)? {
+                detections.push(index);
+            }
+        }
+        Ok(detections)
+    }
+
+    #[test]
+    fn test_template_detector_matches_keyword() -> Result<()> {
+        let keyword = tones(&[300.0, 1200.0, 600.0]);
+        let other = tones(&[2500.0, 150.0, 3500.0]);
+        let mut detector =
+            TemplateDetector::from_recordings(&[(keyword.clone(), 0.5), (other.clone(), 0.5)])?;
+
+        let mut stream = vec![0.0; 8000];
+        stream.extend(&keyword);
+        stream.extend(vec![0.0; 8000]);
+        assert_eq!(detections(&mut detector, &stream)?, vec![0]);
+
+        let mut stream = vec![0.0; 8000];
+        stream.extend(&other);
+        stream.extend(vec![0.0; 8000]);
+        assert_eq!(detections(&mut detector, &stream)?, vec![1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_detector_reports_keyword_end() -> Result<()> {
+        let keyword = tones(&[300.0, 1200.0, 600.0]);
+        let mut detector = TemplateDetector::from_recordings(&[(keyword.clone(), 0.5)])?;
+
+        let mut stream = vec![0.0; 8000];
+        let keyword_start = stream.len();
+        stream.extend(&keyword);
+        let keyword_end = stream.len();
+        stream.extend(vec![0.0; 8000]);
+
+        let mut detected_at = None;
+        for (index, chunk) in stream.chunks(HOP_LENGTH).enumerate() {
+            let frame: Vec<i16> = chunk.iter().map(|&s| to_i16(s)).collect();
+            if detector.process(&frame)?.is_some() {
+                detected_at = Some((index + 1) * HOP_LENGTH);
+                break;
+            }
+        }
+        let detected_at = detected_at.expect("keyword was not detected");
+        let reported_end = detected_at - detector.keyword_end_offset();
+        assert!(reported_end > keyword_start);
+        assert!(reported_end <= keyword_end + FRAME_LENGTH);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_detector_ignores_silence_and_other_sounds() -> Result<()> {
+        let keyword = tones(&[300.0, 1200.0, 600.0]);
+        let mut detector = TemplateDetector::from_recordings(&[(keyword, 0.5)])?;
+
+        let mut stream = vec![0.0; 16000];
+        stream.extend(tones(&[3000.0, 3000.0, 3000.0]));
+        stream.extend(vec![0.0; 8000]);
+        assert!(detections(&mut detector, &stream)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_without_speech_is_rejected() {
+        assert!(TemplateDetector::from_recordings(&[(vec![0.0; 16000], 0.5)]).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::audio::{count_to_f32, sample_count};
+use std::time::Duration;
+
+// Ends after `hang_time` of silence following speech, or after `no_speech_timeout` without any speech.
+pub struct EnergyVad {
+    threshold: f32,
+    hang_samples: usize,
+    timeout_samples: usize,
+    speech_detected: bool,
+    silent_samples: usize,
+    ended: bool,
+}
+
+impl EnergyVad {
+    pub fn new(
+        threshold: f32,
+        hang_time: Duration,
+        no_speech_timeout: Duration,
+        sample_rate: u32,
+    ) -> Self {
+        Self {
+            threshold,
+            hang_samples: sample_count(hang_time, sample_rate),
+            timeout_samples: sample_count(no_speech_timeout, sample_rate),
+            speech_detected: false,
+            silent_samples: 0,
+            ended: false,
+        }
+    }
+
+    pub const fn reset(&mut self) {
+        self.speech_detected = false;
+        self.silent_samples = 0;
+        self.ended = false;
+    }
+
+    pub const fn heard_speech(&self) -> bool {
+        self.speech_detected
+    }
+
+    fn rms(frame: &[i16]) -> f32 {
+        if frame.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = frame
+            .iter()
+            .map(|&sample| {
+                let sample = f32::from(sample) / 32768.0;
+                sample * sample
+            })
+            .sum();
+        (sum / count_to_f32(frame.len())).sqrt()
+    }
+
+    pub fn detect_onset(&mut self, frame: &[i16]) -> bool {
+        let was_speaking = self.speech_detected;
+        if self.process(frame) {
+            self.reset();
+        }
+        !was_speaking && self.speech_detected
+    }
+
+    pub fn process(&mut self, frame: &[i16]) -> bool {
+        if self.ended {
+            return false;
+        }
+
+        if Self::rms(frame) >= self.threshold {
+            self.speech_detected = true;
+            self.silent_samples = 0;
+        } else {
+            self.silent_samples += frame.len();
+        }
+
+        self.ended = if self.speech_detected {
+            self.silent_samples >= self.hang_samples
+        } else {
+            self.silent_samples >= self.timeout_samples
+        };
+        self.ended
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: usize = 512;
+
+    fn frame(amplitude: i16) -> Vec<i16> {
+        (0..FRAME)
+            .map(|i| if i % 2 == 0 { amplitude } else { -amplitude })
+            .collect()
+    }
+
+    #[test]
+    fn test_energy_vad_ends_after_hang_time() {
+        let mut vad = EnergyVad::new(
+            0.05,
+            Duration::from_millis(96),
+            Duration::from_secs(10),
+            16000,
+        );
+        let hang_frames = 96 * 16 / FRAME;
+
+        assert!(!vad.process(&frame(0)));
+        assert!(!vad.process(&frame(8000)));
+        for _ in 1..hang_frames {
+            assert!(!vad.process(&frame(10)));
+        }
+        assert!(vad.process(&frame(10)));
+        assert!(!vad.process(&frame(10)));
+    }
+
+    #[test]
+    fn test_energy_vad_waits_for_speech() {
+        let mut vad = EnergyVad::new(
+            0.05,
+            Duration::from_millis(32),
+            Duration::from_secs(10),
+            16000,
+        );
+
+        for _ in 0..100 {
+            assert!(!vad.process(&frame(10)));
+        }
+
+        assert!(!vad.process(&frame(8000)));
+        assert!(vad.process(&frame(10)));
+
+        vad.reset();
+        assert!(!vad.process(&frame(10)));
+    }
+
+    #[test]
+    fn test_energy_vad_detects_each_onset_once() {
+        let mut vad = EnergyVad::new(
+            0.05,
+            Duration::from_millis(32),
+            Duration::from_secs(10),
+            16000,
+        );
+
+        assert!(!vad.detect_onset(&frame(10)));
+        assert!(vad.detect_onset(&frame(8000)));
+        assert!(!vad.detect_onset(&frame(8000)));
+        assert!(!vad.detect_onset(&frame(10)));
+        assert!(!vad.detect_onset(&frame(10)));
+        assert!(vad.detect_onset(&frame(8000)));
+    }
+
+    #[test]
+    fn test_energy_vad_gives_up_without_speech() {
+        let mut vad = EnergyVad::new(
+            0.05,
+            Duration::from_millis(32),
+            Duration::from_millis(96),
+            16000,
+        );
+        let timeout_frames = 96 * 16 / FRAME;
+
+        for _ in 1..timeout_frames {
+            assert!(!vad.process(&frame(10)));
+        }
+        assert!(vad.process(&frame(10)));
+        assert!(!vad.heard_speech());
+
+        vad.reset();
+        assert!(!vad.process(&frame(8000)));
+        assert!(vad.process(&frame(10)));
+        assert!(vad.heard_speech());
+    }
+}
@@ -0,0 +1,11 @@
+use crate::error::Result;
+
+pub trait WakeWordDetector: Send {
+    fn frame_length(&self) -> usize;
+    fn process(&mut self, frame: &[i16]) -> Result<Option<usize>>;
+
+    // Samples between the end of the last detected keyword and the end of the frame that detected it.
+    fn keyword_end_offset(&self) -> usize {
+        0
+    }
+}
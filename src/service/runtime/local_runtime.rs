@@ -1,29 +1,42 @@
 use super::runtime_service::RuntimeService;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::model::action::{Action, EntityValue, IntentKind};
+use crate::model::alarm::{Alarm, AlarmSchedule};
+use crate::model::timer::Timer;
 use crate::service::geocoding::GeocodingService;
 use crate::service::llm::LlmService;
+use crate::service::timer::alarm_service::AlarmService;
 use crate::service::timer::timer_service::TimerService;
 use crate::service::volume::VolumeService;
 use crate::service::weather::WeatherService;
 use crate::service::workspace::WorkspaceService;
 use async_trait::async_trait;
 use futures::stream::{self, BoxStream, StreamExt};
-use log::info;
-use std::sync::Arc;
+use jiff::civil::{Time, Weekday};
+use jiff::Zoned;
+use log::{error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::fs;
+
+const MIN_TRANSCRIPT_CONFIDENCE: f32 = 0.7;
 
 pub struct LocalRuntime {
+    alarm_service: Arc<dyn AlarmService>,
     geocoding_service: Arc<dyn GeocodingService>,
     llm_service: Arc<dyn LlmService>,
     weather_service: Arc<dyn WeatherService>,
     timer_service: Arc<dyn TimerService>,
     volume_service: Arc<dyn VolumeService>,
     workspace_service: Arc<dyn WorkspaceService>,
+    locations: Mutex<Vec<String>>,
+    location_storage: Option<PathBuf>,
 }
 
 impl LocalRuntime {
     pub fn new(
+        alarm_service: Arc<dyn AlarmService>,
         geocoding_service: Arc<dyn GeocodingService>,
         llm_service: Arc<dyn LlmService>,
         weather_service: Arc<dyn WeatherService>,
@@ -32,12 +45,68 @@ impl LocalRuntime {
         workspace_service: Arc<dyn WorkspaceService>,
     ) -> Self {
         Self {
+            alarm_service,
             geocoding_service,
             llm_service,
             weather_service,
             timer_service,
             volume_service,
             workspace_service,
+            locations: Mutex::new(Vec::new()),
+            location_storage: None,
+        }
+    }
+
+    pub async fn with_location_storage(mut self, path: PathBuf) -> Self {
+        match Self::load_locations(&path).await {
+            Ok(stored) => {
+                info!("Loaded {} locations from {}", stored.len(), path.display());
+                self.locations = Mutex::new(stored);
+                self.location_storage = Some(path);
+            }
+            Err(e) => {
+                error!("Failed to load locations from {}: {}", path.display(), e);
+                warn!("Falling back to in-memory locations");
+            }
+        }
+        self
+    }
+
+    async fn load_locations(path: &Path) -> Result<Vec<String>> {
+        match fs::read(path).await {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn remember_location(&self, location: &str) {
+        let stored = {
+            let Ok(mut locations) = self.locations.lock() else {
+                return;
+            };
+            if locations.iter().any(|known| known == location) {
+                return;
+            }
+            locations.push(location.to_string());
+            locations.clone()
+        };
+
+        let Some(path) = &self.location_storage else {
+            return;
+        };
+
+        let result = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(path, serde_json::to_vec_pretty(&stored)?).await?;
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            error!("Failed to save locations to {}: {}", path.display(), e);
         }
     }
 
@@ -46,11 +115,240 @@ impl LocalRuntime {
     ) -> Result<BoxStream<'static, Result<String>>> {
         Ok(stream::once(async { Ok(s.into()) }).boxed())
     }
+
+    fn format_duration(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        let parts: Vec<String> = [
+            (secs / 3600, "hour"),
+            (secs / 60 % 60, "minute"),
+            (secs % 60, "second"),
+        ]
+        .into_iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{} {}{}", value, unit, if value == 1 { "" } else { "s" }))
+        .collect();
+
+        match parts.as_slice() {
+            [] => "0 seconds".to_string(),
+            [rest @ .., last] if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            _ => parts.join(""),
+        }
+    }
+
+    fn timer_name(timer: &Timer) -> String {
+        if timer.description.split_whitespace().count() == 1 {
+            format!("the {} timer", timer.description)
+        } else {
+            format!("the {} timer", Self::format_duration(timer.duration))
+        }
+    }
+
+    fn string_entity<'a>(action: &'a Action, name: &str) -> Option<&'a str> {
+        action
+            .entities
+            .iter()
+            .find(|e| e.entity == name)
+            .and_then(|e| match &e.value {
+                EntityValue::String(s) => Some(s.as_str()),
+                _ => None,
+            })
+    }
+
+    async fn manage_timers(&self, action: &Action) -> Result<String> {
+        let label = Self::string_entity(action, "label");
+        let all = Self::string_entity(action, "scope") == Some("all");
+
+        let timers: Vec<Timer> = self
+            .timer_service
+            .list()
+            .await?
+            .into_iter()
+            .filter(|timer| {
+                label.is_none_or(|label| timer.description.to_lowercase().contains(label))
+            })
+            .collect();
+
+        if timers.is_empty() {
+            return Ok(label.map_or_else(
+                || "You have no timers.".to_string(),
+                |label| format!("I couldn't find a {label} timer."),
+            ));
+        }
+
+        if timers.len() > 1 && !all && action.intent.name != IntentKind::TimerRemaining {
+            let names: Vec<String> = timers.iter().map(Self::timer_name).collect();
+            return Ok(format!(
+                "You have {} timers: {}. Which one do you mean?",
+                timers.len(),
+                names.join(", ")
+            ));
+        }
+
+        let mut replies = Vec::new();
+        for timer in timers {
+            let name = Self::timer_name(&timer);
+            let reply = match action.intent.name {
+                IntentKind::CancelTimer => {
+                    self.timer_service.cancel(timer.id).await?;
+                    format!("Cancelled {name}.")
+                }
+                IntentKind::PauseTimer => {
+                    self.timer_service.pause(timer.id).await?;
+                    format!("Paused {name}.")
+                }
+                IntentKind::ResumeTimer => {
+                    self.timer_service.resume(timer.id).await?;
+                    format!("Resumed {name}.")
+                }
+                _ => {
+                    let remaining = self.timer_service.remaining(timer.id).await?;
+                    format!(
+                        "{} has {} left.",
+                        Self::capitalize(&name),
+                        Self::format_duration(remaining)
+                    )
+                }
+            };
+            replies.push(reply);
+        }
+
+        Ok(replies.join(" "))
+    }
+
+    fn capitalize(s: &str) -> String {
+        let mut chars = s.chars();
+        chars.next().map_or_else(String::new, |first| {
+            first.to_uppercase().chain(chars).collect()
+        })
+    }
+
+    fn alarm_time(action: &Action) -> Option<Time> {
+        action
+            .entities
+            .iter()
+            .find(|e| e.entity == "time")
+            .and_then(|e| match &e.value {
+                EntityValue::Time(t) => Time::new(
+                    i8::try_from(t.hour).ok()?,
+                    i8::try_from(t.minute).ok()?,
+                    0,
+                    0,
+                )
+                .ok(),
+                _ => None,
+            })
+    }
+
+    fn alarm_schedule(action: &Action, time: Time) -> AlarmSchedule {
+        let mut days: Vec<Weekday> = action
+            .entities
+            .iter()
+            .filter(|e| e.entity == "day")
+            .filter_map(|e| match &e.value {
+                EntityValue::String(day) => match day.as_str() {
+                    "monday" => Some(Weekday::Monday),
+                    "tuesday" => Some(Weekday::Tuesday),
+                    "wednesday" => Some(Weekday::Wednesday),
+                    "thursday" => Some(Weekday::Thursday),
+                    "friday" => Some(Weekday::Friday),
+                    "saturday" => Some(Weekday::Saturday),
+                    "sunday" => Some(Weekday::Sunday),
+                    "tomorrow" => Some(Zoned::now().date().tomorrow().ok()?.weekday()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        days.sort_by_key(|day| day.to_monday_zero_offset());
+        days.dedup();
+
+        let recurring = Self::string_entity(action, "repeat").is_some();
+        AlarmSchedule::new(time, days, recurring)
+    }
+
+    fn format_time(time: Time) -> String {
+        let hour = match time.hour() % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let meridiem = if time.hour() < 12 { "AM" } else { "PM" };
+        format!("{}:{:02} {}", hour, time.minute(), meridiem)
+    }
+
+    fn describe_alarm(alarm: &Alarm) -> String {
+        let time = Self::format_time(alarm.schedule.time);
+        let days = &alarm.schedule.days;
+
+        if alarm.schedule.recurring {
+            let names: Vec<String> = days.iter().map(|day| format!("{day:?}")).collect();
+            return match days.len() {
+                0 | 7 => format!("{time} every day"),
+                5 if !days.contains(&Weekday::Saturday) && !days.contains(&Weekday::Sunday) => {
+                    format!("{time} every weekday")
+                }
+                _ => format!("{} every {}", time, names.join(" and ")),
+            };
+        }
+
+        let today = Zoned::now().date();
+        match alarm.next.date() {
+            date if date == today => format!("{time} today"),
+            date if today.tomorrow().is_ok_and(|tomorrow| tomorrow == date) => {
+                format!("{time} tomorrow")
+            }
+            date => format!("{} on {:?}", time, date.weekday()),
+        }
+    }
+
+    async fn cancel_alarms(&self, action: &Action) -> Result<String> {
+        let time = Self::alarm_time(action);
+        let all = Self::string_entity(action, "scope") == Some("all");
+
+        let alarms: Vec<Alarm> = self
+            .alarm_service
+            .list()
+            .await?
+            .into_iter()
+            .filter(|alarm| time.is_none_or(|time| alarm.schedule.time == time))
+            .collect();
+
+        if alarms.is_empty() {
+            return Ok(time.map_or_else(
+                || "You have no alarms.".to_string(),
+                |time| format!("I couldn't find an alarm at {}.", Self::format_time(time)),
+            ));
+        }
+
+        if alarms.len() > 1 && !all && time.is_none() {
+            return Ok(format!(
+                "You have {} alarms. Which one do you mean?",
+                alarms.len()
+            ));
+        }
+
+        for alarm in &alarms {
+            self.alarm_service.cancel(alarm.id).await?;
+        }
+
+        Ok(match alarms.as_slice() {
+            [alarm] => format!("Cancelled the alarm for {}.", Self::describe_alarm(alarm)),
+            _ => format!("Cancelled {} alarms.", alarms.len()),
+        })
+    }
 }
 
 #[async_trait]
 impl RuntimeService for LocalRuntime {
     async fn run(&self, action: Action) -> Result<BoxStream<'static, Result<String>>> {
+        if let Some(confidence) = action.transcript_confidence {
+            if confidence < MIN_TRANSCRIPT_CONFIDENCE {
+                return Self::string_stream(format!(
+                    "I'm not sure I heard you correctly. Did you say \"{}\"?",
+                    action.text
+                ));
+            }
+        }
+
         if let Some(confidence) = action.intent.confidence {
             if action.intent.name != IntentKind::LlmQuery && confidence < 0.9 {
                 return Self::string_stream(
@@ -124,17 +422,86 @@ impl RuntimeService for LocalRuntime {
                     .iter()
                     .find(|e| e.entity == "duration")
                     .and_then(|e| match &e.value {
-                        EntityValue::Duration(d) => Some(Duration::from_secs(d.value)),
+                        EntityValue::Duration(d) => Some(d.to_duration()),
                         _ => None,
                     });
+                let description = Self::string_entity(&action, "label")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| action.text.clone());
 
                 let response = match duration {
-                    Some(duration) => self.timer_service.set(duration, action.text).await?,
+                    Some(duration) => self.timer_service.set(duration, description).await?,
                     None => "Please specify a clear duration for the timer.".to_string(),
                 };
 
                 Self::string_stream(response)
             }
+            IntentKind::ListTimers => {
+                let timers = self.timer_service.list().await?;
+                if timers.is_empty() {
+                    return Self::string_stream("You have no timers.");
+                }
+
+                let descriptions: Vec<String> = timers
+                    .iter()
+                    .map(|timer| {
+                        let remaining = Self::format_duration(timer.remaining());
+                        if timer.is_paused() {
+                            format!(
+                                "{}, paused with {} left",
+                                Self::timer_name(timer),
+                                remaining
+                            )
+                        } else {
+                            format!("{} with {} left", Self::timer_name(timer), remaining)
+                        }
+                    })
+                    .collect();
+
+                Self::string_stream(format!(
+                    "You have {} timer{}: {}.",
+                    timers.len(),
+                    if timers.len() == 1 { "" } else { "s" },
+                    descriptions.join("; ")
+                ))
+            }
+            IntentKind::SetAlarm => {
+                let response = match Self::alarm_time(&action) {
+                    Some(time) => {
+                        let schedule = Self::alarm_schedule(&action, time);
+                        let alarm = self.alarm_service.set(schedule, action.text).await?;
+                        format!("Alarm set for {}.", Self::describe_alarm(&alarm))
+                    }
+                    None => "Please specify a time for the alarm.".to_string(),
+                };
+
+                Self::string_stream(response)
+            }
+            IntentKind::ListAlarms => {
+                let alarms = self.alarm_service.list().await?;
+                if alarms.is_empty() {
+                    return Self::string_stream("You have no alarms.");
+                }
+
+                let descriptions: Vec<String> = alarms.iter().map(Self::describe_alarm).collect();
+                Self::string_stream(format!(
+                    "You have {} alarm{}: {}.",
+                    alarms.len(),
+                    if alarms.len() == 1 { "" } else { "s" },
+                    descriptions.join("; ")
+                ))
+            }
+            IntentKind::CancelAlarm => {
+                let response = self.cancel_alarms(&action).await?;
+                Self::string_stream(response)
+            }
+            IntentKind::CancelTimer
+            | IntentKind::PauseTimer
+            | IntentKind::ResumeTimer
+            | IntentKind::TimerRemaining => {
+                let response = self.manage_timers(&action).await?;
+                Self::string_stream(response)
+            }
             IntentKind::ShowDesktop => {
                 self.workspace_service.show_desktop().await?;
                 Self::string_stream("Desktop shown.")
@@ -188,6 +555,7 @@ impl RuntimeService for LocalRuntime {
                             EntityValue::String(location) => {
                                 let geocode = self.geocoding_service.request(location).await?;
                                 info!("Geocode received: {:?}", &geocode);
+                                self.remember_location(location).await;
                                 let weather_response = self.weather_service.request(geocode).await?;
                                 info!("Weather response received: {:?}", &weather_response);
                                 Self::string_stream(weather_response)
@@ -206,4 +574,27 @@ impl RuntimeService for LocalRuntime {
             }
         }
     }
+
+    async fn vocabulary(&self) -> Result<Vec<String>> {
+        let mut vocabulary: Vec<String> = self
+            .timer_service
+            .list()
+            .await?
+            .into_iter()
+            .filter(|timer| timer.description.split_whitespace().count() == 1)
+            .map(|timer| timer.description)
+            .collect();
+        if let Ok(locations) = self.locations.lock() {
+            vocabulary.extend(locations.iter().cloned());
+        }
+        match self.workspace_service.workspace_names().await {
+            Ok(names) => vocabulary.extend(names),
+            Err(e) => warn!("Failed to read workspace names: {e}"),
+        }
+        match self.workspace_service.app_names().await {
+            Ok(names) => vocabulary.extend(names),
+            Err(e) => warn!("Failed to read app names: {e}"),
+        }
+        Ok(vocabulary)
+    }
 }
@@ -6,4 +6,8 @@ use futures::stream::BoxStream;
 #[async_trait]
 pub trait RuntimeService: Send + Sync {
     async fn run(&self, action: Action) -> Result<BoxStream<'static, Result<String>>>;
+
+    async fn vocabulary(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 }
@@ -48,8 +48,7 @@ impl ElevenLabsClient {
             self.base_url, self.voice_id
         );
 
-        let url =
-            Url::parse(&ws_url).map_err(|e| Error::ApiError(format!("Invalid URL: {}", e)))?;
+        let url = Url::parse(&ws_url).map_err(|e| Error::ApiError(format!("Invalid URL: {e}")))?;
 
         let url_str = url.as_str();
 
@@ -62,11 +61,11 @@ impl ElevenLabsClient {
             .header("Sec-WebSocket-Version", "13")
             .header("xi-api-key", &self.api_key)
             .body(())
-            .map_err(|e| Error::ApiError(format!("Failed to build request: {}", e)))?;
+            .map_err(|e| Error::ApiError(format!("Failed to build request: {e}")))?;
 
         let (ws_stream, _) = connect_async(request)
             .await
-            .map_err(|e| Error::ApiError(format!("Failed to connect to WebSocket: {}", e)))?;
+            .map_err(|e| Error::ApiError(format!("Failed to connect to WebSocket: {e}")))?;
 
         Ok(ws_stream)
     }
@@ -98,8 +97,7 @@ impl Stream for AudioStream {
                                         .decode(audio_str)
                                         .map_err(|e| {
                                             Error::ApiError(format!(
-                                                "Failed to decode base64 audio: {}",
-                                                e
+                                                "Failed to decode base64 audio: {e}"
                                             ))
                                         })
                                         .map(Bytes::from),
@@ -108,13 +106,11 @@ impl Stream for AudioStream {
                         }
 
                         Poll::Ready(Some(Err(Error::ApiError(format!(
-                            "Received message not containing audio from ElevenLabs: {}",
-                            parsed
+                            "Received message not containing audio from ElevenLabs: {parsed}"
                         )))))
                     }
                     Err(e) => Poll::Ready(Some(Err(Error::ApiError(format!(
-                        "Failed to parse ElevenLabs response JSON: {}",
-                        e
+                        "Failed to parse ElevenLabs response JSON: {e}"
                     ))))),
                 },
                 Message::Close(_) => Poll::Ready(None),
@@ -123,8 +119,7 @@ impl Stream for AudioStream {
                 )))),
             },
             Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(Error::ApiError(format!(
-                "ElevenLabs WebSocket error: {}",
-                e
+                "ElevenLabs WebSocket error: {e}"
             ))))),
             Poll::Ready(None) => Poll::Ready(None),
             Poll::Pending => Poll::Pending,
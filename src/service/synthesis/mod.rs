@@ -1,7 +1,9 @@
 pub mod elevenlabs_client;
 pub mod piper_client;
+pub mod sentence_segmenter;
 pub mod synthesizer_service;
 
 pub use elevenlabs_client::ElevenLabsClient;
 pub use piper_client::PiperClient;
+pub use sentence_segmenter::synthesize_sentences;
 pub use synthesizer_service::SynthesizerService;
@@ -5,10 +5,7 @@ use crate::error::{
 };
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::{
-    stream::{once, BoxStream},
-    StreamExt,
-};
+use futures::{stream::BoxStream, StreamExt};
 use reqwest::Client;
 use serde_json::json;
 use url::Url;
@@ -51,11 +48,10 @@ impl SynthesizerService for PiperClient {
             )));
         }
 
-        let audio = response
-            .bytes()
-            .await
-            .map_err(|e| Error::ApiError(format!("Failed to read response bytes: {}", e)))?;
+        let audio_stream = response.bytes_stream().map(|chunk| {
+            chunk.map_err(|e| Error::ApiError(format!("Failed to read response bytes: {}", e)))
+        });
 
-        Ok(Box::pin(once(async move { Ok(audio) })))
+        Ok(audio_stream.boxed())
     }
 }
@@ -0,0 +1,141 @@
+use super::SynthesizerService;
+use crate::error::Result;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::Arc;
+
+const SENTENCE_TERMINATORS: [char; 4] = ['.', '!', '?', '\n'];
+const PREFETCHED_SENTENCES: usize = 2;
+
+pub fn segment_sentences(
+    text: BoxStream<'static, Result<String>>,
+) -> BoxStream<'static, Result<String>> {
+    stream::unfold(
+        (text, String::new(), false),
+        |(mut text, mut buffer, finished)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                if let Some(sentence) = take_sentence(&mut buffer) {
+                    return Some((Ok(sentence), (text, buffer, false)));
+                }
+                match text.next().await {
+                    Some(Ok(chunk)) => buffer.push_str(&chunk),
+                    Some(Err(e)) => return Some((Err(e), (text, buffer, true))),
+                    None => {
+                        let rest = buffer.trim().to_string();
+                        return (!rest.is_empty()).then(|| (Ok(rest), (text, buffer, true)));
+                    }
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+pub fn synthesize_sentences(
+    synthesizer: Arc<dyn SynthesizerService>,
+    text: BoxStream<'static, Result<String>>,
+) -> BoxStream<'static, Result<BoxStream<'static, Result<Bytes>>>> {
+    segment_sentences(text)
+        .map(move |sentence| {
+            let synthesizer = synthesizer.clone();
+            async move {
+                let sentence = sentence?;
+                synthesizer
+                    .synthesize(stream::once(async { Ok(sentence) }).boxed())
+                    .await
+            }
+        })
+        .buffered(PREFETCHED_SENTENCES)
+        .boxed()
+}
+
+fn take_sentence(buffer: &mut String) -> Option<String> {
+    let mut chars = buffer.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if !SENTENCE_TERMINATORS.contains(&c) {
+            continue;
+        }
+        let &(next_index, next) = chars.peek()?;
+        if !next.is_whitespace() {
+            continue;
+        }
+        let sentence = buffer[..=index].trim().to_string();
+        buffer.drain(..next_index);
+        if sentence.is_empty() {
+            return take_sentence(buffer);
+        }
+        return Some(sentence);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn text_stream(chunks: &[&str]) -> BoxStream<'static, Result<String>> {
+        let chunks: Vec<Result<String>> = chunks.iter().map(|c| Ok(c.to_string())).collect();
+        stream::iter(chunks).boxed()
+    }
+
+    #[tokio::test]
+    async fn test_segment_sentences_across_chunks() -> Result<()> {
+        let sentences = segment_sentences(text_stream(&[
+            "Hello th",
+            "ere! It is 3.5 degr",
+            "ees in Vienna. Anything",
+            " else?",
+        ]))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            sentences,
+            vec![
+                "Hello there!",
+                "It is 3.5 degrees in Vienna.",
+                "Anything else?"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_segment_sentences_skips_blank_segments() -> Result<()> {
+        let sentences = segment_sentences(text_stream(&["First.\n\n", "  \n", "Second"]))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(sentences, vec!["First.", "Second"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_segment_sentences_stops_after_error() {
+        let text = stream::iter(vec![
+            Ok("Complete sentence. Incomplete".to_string()),
+            Err(Error::ApiError("stream broke".to_string())),
+            Ok("Never seen.".to_string()),
+        ])
+        .boxed();
+
+        let results = segment_sentences(text).collect::<Vec<_>>().await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].as_ref().ok().map(String::as_str),
+            Some("Complete sentence.")
+        );
+        assert!(results[1].is_err());
+    }
+}
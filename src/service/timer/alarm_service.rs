@@ -0,0 +1,14 @@
+use crate::error::Result;
+use crate::model::alarm::{Alarm, AlarmSchedule};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait AlarmService: Send + Sync {
+    async fn set(&self, schedule: AlarmSchedule, description: String) -> Result<Alarm>;
+    async fn list(&self) -> Result<Vec<Alarm>>;
+    async fn cancel(&self, id: u64) -> Result<Alarm>;
+
+    async fn missed(&self) -> Result<Vec<Alarm>> {
+        Ok(Vec::new())
+    }
+}
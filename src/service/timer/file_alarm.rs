@@ -0,0 +1,164 @@
+use super::alarm_service::AlarmService;
+use super::memory_alarm::MemoryAlarm;
+use crate::error::{Error, Result};
+use crate::model::alarm::{Alarm, AlarmSchedule};
+use crate::model::event::Event;
+use async_trait::async_trait;
+use directories::ProjectDirs;
+use log::{error, info, warn};
+use notify_rust::Notification;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::sync::{broadcast, Mutex};
+
+pub struct FileAlarm {
+    inner: MemoryAlarm,
+    missed: Mutex<Vec<Alarm>>,
+}
+
+impl FileAlarm {
+    pub async fn new(events: broadcast::Sender<Event>) -> Result<Self> {
+        let dirs = ProjectDirs::from("", "", "voice").ok_or(Error::DataDirectoryNotFound)?;
+        Self::with_path(events, dirs.data_dir().join("alarms.json")).await
+    }
+
+    pub async fn with_path(events: broadcast::Sender<Event>, path: PathBuf) -> Result<Self> {
+        let stored: Vec<Alarm> = match fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        info!("Loaded {} alarms from {}", stored.len(), path.display());
+
+        let inner = MemoryAlarm::with_storage(events, path);
+        let missed = inner.restore(stored).await;
+        for alarm in &missed {
+            warn!(
+                "Alarm went off while the server was offline: {}",
+                alarm.description
+            );
+
+            if let Err(e) = Notification::new()
+                .summary("Alarm missed while offline")
+                .body(&alarm.description)
+                .show()
+            {
+                error!("Failed to show alarm notification: {e}");
+            }
+        }
+
+        Ok(Self {
+            inner,
+            missed: Mutex::new(missed),
+        })
+    }
+}
+
+#[async_trait]
+impl AlarmService for FileAlarm {
+    async fn set(&self, schedule: AlarmSchedule, description: String) -> Result<Alarm> {
+        self.inner.set(schedule, description).await
+    }
+
+    async fn list(&self) -> Result<Vec<Alarm>> {
+        self.inner.list().await
+    }
+
+    async fn cancel(&self, id: u64) -> Result<Alarm> {
+        self.inner.cancel(id).await
+    }
+
+    async fn missed(&self) -> Result<Vec<Alarm>> {
+        Ok(std::mem::take(&mut *self.missed.lock().await))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::time;
+    use jiff::{ToSpan, Zoned};
+
+    fn storage_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("voice-backend-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[tokio::test]
+    async fn test_file_alarm_restores_alarms() -> Result<()> {
+        let path = storage_path("alarms.json");
+        let (events, _) = broadcast::channel(1);
+
+        let alarm_service = FileAlarm::with_path(events.clone(), path.clone()).await?;
+        let wake_up = alarm_service
+            .set(
+                AlarmSchedule::new(time(7, 30, 0, 0), Vec::new(), true),
+                "wake up".to_string(),
+            )
+            .await?;
+        let stretch = alarm_service
+            .set(
+                AlarmSchedule::new(time(12, 0, 0, 0), Vec::new(), false),
+                "stretch".to_string(),
+            )
+            .await?;
+        alarm_service.cancel(stretch.id).await?;
+        drop(alarm_service);
+
+        let alarm_service = FileAlarm::with_path(events, path.clone()).await?;
+        let alarms = alarm_service.list().await?;
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].description, "wake up");
+        assert_eq!(alarms[0].schedule, wake_up.schedule);
+        assert_eq!(alarms[0].next, wake_up.next);
+        let tea = alarm_service
+            .set(
+                AlarmSchedule::new(time(16, 0, 0, 0), Vec::new(), false),
+                "tea".to_string(),
+            )
+            .await?;
+        assert_eq!(tea.id, wake_up.id + 1);
+
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_alarm_handles_missed_alarms() -> Result<()> {
+        let path = storage_path("missed_alarms.json");
+        let an_hour_ago = Zoned::now().checked_sub(1.hour()).unwrap();
+        let schedule = |recurring| AlarmSchedule::new(an_hour_ago.time(), Vec::new(), recurring);
+        let stored = vec![
+            Alarm {
+                id: 1,
+                schedule: schedule(false),
+                description: "dentist".to_string(),
+                next: an_hour_ago.clone(),
+            },
+            Alarm {
+                id: 2,
+                schedule: schedule(true),
+                description: "medication".to_string(),
+                next: an_hour_ago.clone(),
+            },
+        ];
+        fs::create_dir_all(path.parent().unwrap()).await?;
+        fs::write(&path, serde_json::to_vec(&stored)?).await?;
+
+        let (events, _) = broadcast::channel(1);
+        let alarm_service = FileAlarm::with_path(events, path.clone()).await?;
+
+        let alarms = alarm_service.list().await?;
+        assert_eq!(alarms.len(), 1);
+        assert_eq!(alarms[0].description, "medication");
+        assert!(alarms[0].next > Zoned::now());
+        let missed = alarm_service.missed().await?;
+        assert_eq!(missed.len(), 2);
+        assert_eq!(missed[0].description, "dentist");
+        assert!(alarm_service.missed().await?.is_empty());
+
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+}
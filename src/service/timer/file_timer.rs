@@ -0,0 +1,159 @@
+use super::memory_timer::MemoryTimer;
+use super::timer_service::TimerService;
+use crate::error::{Error, Result};
+use crate::model::event::Event;
+use crate::model::timer::Timer;
+use async_trait::async_trait;
+use directories::ProjectDirs;
+use log::{error, info, warn};
+use notify_rust::Notification;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::fs;
+use tokio::sync::{broadcast, Mutex};
+
+pub struct FileTimer {
+    inner: MemoryTimer,
+    missed: Mutex<Vec<Timer>>,
+}
+
+impl FileTimer {
+    pub async fn new(events: broadcast::Sender<Event>) -> Result<Self> {
+        let dirs = ProjectDirs::from("", "", "voice").ok_or(Error::DataDirectoryNotFound)?;
+        Self::with_path(events, dirs.data_dir().join("timers.json")).await
+    }
+
+    pub async fn with_path(events: broadcast::Sender<Event>, path: PathBuf) -> Result<Self> {
+        let stored: Vec<Timer> = match fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        info!("Loaded {} timers from {}", stored.len(), path.display());
+
+        let inner = MemoryTimer::with_storage(events, path);
+        let missed = inner.restore(stored).await;
+        for timer in &missed {
+            warn!(
+                "Timer expired while the server was offline: {}",
+                timer.description
+            );
+
+            if let Err(e) = Notification::new()
+                .summary("Timer expired while offline")
+                .body(&timer.description)
+                .show()
+            {
+                error!("Failed to show timer notification: {e}");
+            }
+        }
+
+        Ok(Self {
+            inner,
+            missed: Mutex::new(missed),
+        })
+    }
+}
+
+#[async_trait]
+impl TimerService for FileTimer {
+    async fn set(&self, duration: Duration, description: String) -> Result<String> {
+        self.inner.set(duration, description).await
+    }
+
+    async fn list(&self) -> Result<Vec<Timer>> {
+        self.inner.list().await
+    }
+
+    async fn cancel(&self, id: u64) -> Result<Timer> {
+        self.inner.cancel(id).await
+    }
+
+    async fn pause(&self, id: u64) -> Result<Timer> {
+        self.inner.pause(id).await
+    }
+
+    async fn resume(&self, id: u64) -> Result<Timer> {
+        self.inner.resume(id).await
+    }
+
+    async fn remaining(&self, id: u64) -> Result<Duration> {
+        self.inner.remaining(id).await
+    }
+
+    async fn missed(&self) -> Result<Vec<Timer>> {
+        Ok(std::mem::take(&mut *self.missed.lock().await))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::timer::TimerState;
+    use std::time::SystemTime;
+
+    fn storage_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("voice-backend-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[tokio::test]
+    async fn test_file_timer_restores_pending_timers() -> Result<()> {
+        let path = storage_path("pending.json");
+        let (events, _) = broadcast::channel(1);
+
+        let timer_service = FileTimer::with_path(events.clone(), path.clone()).await?;
+        timer_service
+            .set(Duration::from_secs(60), "pasta".to_string())
+            .await?;
+        timer_service
+            .set(Duration::from_secs(120), "tea".to_string())
+            .await?;
+        let tea = timer_service.list().await?[1].id;
+        timer_service.pause(tea).await?;
+        drop(timer_service);
+
+        let timer_service = FileTimer::with_path(events, path.clone()).await?;
+        let timers = timer_service.list().await?;
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers[0].description, "pasta");
+        assert!(!timers[0].is_paused());
+        assert!(timers[1].is_paused());
+        timer_service
+            .set(Duration::from_secs(30), "eggs".to_string())
+            .await?;
+        assert_eq!(timer_service.list().await?[2].id, tea + 1);
+
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_timer_reports_missed_timers() -> Result<()> {
+        let path = storage_path("missed.json");
+        let expired = Timer {
+            id: 1,
+            duration: Duration::from_secs(60),
+            description: "laundry".to_string(),
+            state: TimerState::Running {
+                ends_at: SystemTime::now() - Duration::from_secs(10),
+            },
+        };
+        fs::create_dir_all(path.parent().unwrap()).await?;
+        fs::write(&path, serde_json::to_vec(&vec![expired])?).await?;
+
+        let (events, mut receiver) = broadcast::channel(1);
+        let timer_service = FileTimer::with_path(events, path.clone()).await?;
+
+        assert!(timer_service.list().await?.is_empty());
+        assert!(receiver.try_recv().is_err());
+        let missed = timer_service.missed().await?;
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].description, "laundry");
+        assert!(timer_service.missed().await?.is_empty());
+
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use super::alarm_service::AlarmService;
+use crate::error::{Error, Result};
+use crate::model::alarm::{Alarm, AlarmSchedule};
+use crate::model::event::Event;
+use async_trait::async_trait;
+use jiff::{Timestamp, Zoned};
+use log::{error, info};
+use notify_rust::Notification;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+const MAX_SLEEP: Duration = Duration::from_mins(1);
+
+struct AlarmEntry {
+    alarm: Alarm,
+    task: JoinHandle<()>,
+}
+
+pub struct MemoryAlarm {
+    alarms: Arc<Mutex<HashMap<u64, AlarmEntry>>>,
+    next_id: AtomicU64,
+    events: broadcast::Sender<Event>,
+    storage: Option<PathBuf>,
+}
+
+impl MemoryAlarm {
+    pub fn new(events: broadcast::Sender<Event>) -> Self {
+        Self {
+            alarms: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            events,
+            storage: None,
+        }
+    }
+
+    pub(super) fn with_storage(events: broadcast::Sender<Event>, path: PathBuf) -> Self {
+        Self {
+            alarms: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            events,
+            storage: Some(path),
+        }
+    }
+
+    pub(super) async fn restore(&self, stored: Vec<Alarm>) -> Vec<Alarm> {
+        let mut alarms = self.alarms.lock().await;
+        let mut missed = Vec::new();
+        let now = Zoned::now();
+
+        for mut alarm in stored {
+            self.next_id.fetch_max(alarm.id + 1, Ordering::Relaxed);
+            if alarm.next <= now {
+                missed.push(alarm.clone());
+                let Some(following) = alarm
+                    .schedule
+                    .recurring
+                    .then(|| alarm.schedule.next_after(&now))
+                    .flatten()
+                else {
+                    continue;
+                };
+                alarm.next = following;
+            }
+            let task = self.spawn_alarm(alarm.id, alarm.next.clone());
+            alarms.insert(alarm.id, AlarmEntry { alarm, task });
+        }
+
+        Self::persist(self.storage.as_ref(), &alarms).await;
+        drop(alarms);
+        missed
+    }
+
+    async fn persist(storage: Option<&PathBuf>, alarms: &HashMap<u64, AlarmEntry>) {
+        let Some(path) = storage else {
+            return;
+        };
+
+        let mut stored: Vec<&Alarm> = alarms.values().map(|entry| &entry.alarm).collect();
+        stored.sort_by_key(|alarm| alarm.id);
+
+        let result = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(path, serde_json::to_vec_pretty(&stored)?).await?;
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            error!("Failed to save alarms to {}: {}", path.display(), e);
+        }
+    }
+
+    async fn sleep_until(at: &Zoned) {
+        loop {
+            let remaining = Duration::try_from(at.timestamp().duration_since(Timestamp::now()))
+                .unwrap_or_default();
+            if remaining.is_zero() {
+                return;
+            }
+            // Re-check the wall clock regularly so suspends and clock changes don't delay alarms.
+            sleep(remaining.min(MAX_SLEEP)).await;
+        }
+    }
+
+    fn spawn_alarm(&self, id: u64, mut next: Zoned) -> JoinHandle<()> {
+        let alarms = self.alarms.clone();
+        let events = self.events.clone();
+        let storage = self.storage.clone();
+
+        tokio::spawn(async move {
+            loop {
+                Self::sleep_until(&next).await;
+
+                let (description, rescheduled) = {
+                    let mut alarms = alarms.lock().await;
+                    let Some(entry) = alarms.get_mut(&id) else {
+                        return;
+                    };
+                    let description = entry.alarm.description.clone();
+
+                    let fired = if let Some(following) = entry
+                        .alarm
+                        .schedule
+                        .recurring
+                        .then(|| entry.alarm.schedule.next_after(&next))
+                        .flatten()
+                    {
+                        entry.alarm.next = following.clone();
+                        next = following;
+                        (description, true)
+                    } else {
+                        alarms.remove(&id);
+                        (description, false)
+                    };
+                    Self::persist(storage.as_ref(), &alarms).await;
+                    drop(alarms);
+                    fired
+                };
+                info!("Alarm fired: {description}");
+
+                if let Err(e) = Notification::new()
+                    .summary("Alarm")
+                    .body(&description)
+                    .show()
+                {
+                    error!("Failed to show alarm notification: {e}");
+                }
+
+                let _ = events.send(Event::AlarmFired { description });
+
+                if !rescheduled {
+                    return;
+                }
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl AlarmService for MemoryAlarm {
+    async fn set(&self, schedule: AlarmSchedule, description: String) -> Result<Alarm> {
+        let next = schedule
+            .next_after(&Zoned::now())
+            .ok_or_else(|| Error::AlarmSchedule(format!("No upcoming time for {:?}", schedule)))?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let alarm = Alarm {
+            id,
+            schedule,
+            description,
+            next: next.clone(),
+        };
+
+        let mut alarms = self.alarms.lock().await;
+        let task = self.spawn_alarm(id, next);
+        alarms.insert(
+            id,
+            AlarmEntry {
+                alarm: alarm.clone(),
+                task,
+            },
+        );
+        Self::persist(self.storage.as_ref(), &alarms).await;
+        drop(alarms);
+
+        Ok(alarm)
+    }
+
+    async fn list(&self) -> Result<Vec<Alarm>> {
+        let mut alarms: Vec<Alarm> = self
+            .alarms
+            .lock()
+            .await
+            .values()
+            .map(|entry| entry.alarm.clone())
+            .collect();
+        alarms.sort_by(|a, b| a.next.cmp(&b.next));
+        Ok(alarms)
+    }
+
+    async fn cancel(&self, id: u64) -> Result<Alarm> {
+        let mut alarms = self.alarms.lock().await;
+        let entry = alarms.remove(&id).ok_or(Error::AlarmNotFound(id))?;
+        entry.task.abort();
+        Self::persist(self.storage.as_ref(), &alarms).await;
+        drop(alarms);
+        Ok(entry.alarm)
+    }
+}
+
+impl Drop for MemoryAlarm {
+    fn drop(&mut self) {
+        if let Ok(alarms) = self.alarms.try_lock() {
+            alarms.values().for_each(|entry| entry.task.abort());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::ToSpan;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn test_memory_alarm_fires() -> Result<()> {
+        let (events, mut receiver) = broadcast::channel(1);
+        let alarm_service = MemoryAlarm::new(events);
+        let soon = Zoned::now().checked_add(1.second()).unwrap();
+
+        let alarm = alarm_service
+            .set(
+                AlarmSchedule::new(soon.time(), Vec::new(), false),
+                "stand up".to_string(),
+            )
+            .await?;
+        assert_eq!(alarm.next.time(), soon.time());
+
+        let event = timeout(Duration::from_secs(3), receiver.recv()).await;
+        match event {
+            Ok(Ok(Event::AlarmFired { description })) => assert_eq!(description, "stand up"),
+            other => panic!("Expected an alarm event, got {:?}", other),
+        }
+        assert!(alarm_service.list().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_alarm_cancel() -> Result<()> {
+        let (events, _) = broadcast::channel(1);
+        let alarm_service = MemoryAlarm::new(events);
+        let schedule = AlarmSchedule::new(jiff::civil::time(7, 30, 0, 0), Vec::new(), true);
+
+        let alarm = alarm_service.set(schedule, "wake up".to_string()).await?;
+        assert_eq!(alarm_service.list().await?.len(), 1);
+
+        let cancelled = alarm_service.cancel(alarm.id).await?;
+        assert_eq!(cancelled.description, "wake up");
+        assert!(alarm_service.list().await?.is_empty());
+        assert!(matches!(
+            alarm_service.cancel(alarm.id).await,
+            Err(Error::AlarmNotFound(_))
+        ));
+
+        Ok(())
+    }
+}
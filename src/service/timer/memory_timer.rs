@@ -1,57 +1,224 @@
 use super::timer_service::TimerService;
-use crate::error::Result;
-use crate::model::timer::Timer;
+use crate::error::{Error, Result};
+use crate::model::event::Event;
+use crate::model::timer::{Timer, TimerState};
 use async_trait::async_trait;
-use log::error;
+use log::{error, info};
 use notify_rust::Notification;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::mpsc::channel;
-use tokio::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
+struct TimerEntry {
+    timer: Timer,
+    task: Option<JoinHandle<()>>,
+}
+
 pub struct MemoryTimer {
-    timers: Arc<Mutex<Vec<Timer>>>,
+    timers: Arc<Mutex<HashMap<u64, TimerEntry>>>,
+    next_id: AtomicU64,
+    events: broadcast::Sender<Event>,
+    storage: Option<PathBuf>,
 }
 
 impl MemoryTimer {
-    pub fn new() -> Self {
+    pub fn new(events: broadcast::Sender<Event>) -> Self {
         Self {
-            timers: Arc::new(Mutex::new(Vec::new())),
+            timers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            events,
+            storage: None,
+        }
+    }
+
+    pub(super) fn with_storage(events: broadcast::Sender<Event>, path: PathBuf) -> Self {
+        Self {
+            timers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            events,
+            storage: Some(path),
+        }
+    }
+
+    pub(super) async fn restore(&self, stored: Vec<Timer>) -> Vec<Timer> {
+        let mut timers = self.timers.lock().await;
+        let mut missed = Vec::new();
+
+        for timer in stored {
+            self.next_id.fetch_max(timer.id + 1, Ordering::Relaxed);
+            let task = match timer.state {
+                TimerState::Paused { .. } => None,
+                TimerState::Running { .. } if timer.remaining().is_zero() => {
+                    missed.push(timer);
+                    continue;
+                }
+                TimerState::Running { .. } => {
+                    Some(self.spawn_completion(timer.id, timer.remaining()))
+                }
+            };
+            timers.insert(timer.id, TimerEntry { timer, task });
         }
+
+        Self::persist(self.storage.as_ref(), &timers).await;
+        drop(timers);
+        missed
+    }
+
+    async fn persist(storage: Option<&PathBuf>, timers: &HashMap<u64, TimerEntry>) {
+        let Some(path) = storage else {
+            return;
+        };
+
+        let mut stored: Vec<&Timer> = timers.values().map(|entry| &entry.timer).collect();
+        stored.sort_by_key(|timer| timer.id);
+
+        let result = async {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(path, serde_json::to_vec_pretty(&stored)?).await?;
+            Ok::<_, Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            error!("Failed to save timers to {}: {}", path.display(), e);
+        }
+    }
+
+    fn spawn_completion(&self, id: u64, duration: Duration) -> JoinHandle<()> {
+        let timers = self.timers.clone();
+        let events = self.events.clone();
+        let storage = self.storage.clone();
+
+        tokio::spawn(async move {
+            sleep(duration).await;
+            let timer = {
+                let mut timers = timers.lock().await;
+                let Some(TimerEntry { timer, .. }) = timers.remove(&id) else {
+                    return;
+                };
+                Self::persist(storage.as_ref(), &timers).await;
+                drop(timers);
+                timer
+            };
+            info!("Timer completed: {}", timer.description);
+
+            if let Err(e) = Notification::new()
+                .summary("Timer completed")
+                .body(&timer.description)
+                .show()
+            {
+                error!("Failed to show timer notification: {e}");
+            }
+
+            let _ = events.send(Event::TimerCompleted {
+                description: timer.description,
+            });
+        })
     }
 }
 
 #[async_trait]
 impl TimerService for MemoryTimer {
     async fn set(&self, duration: Duration, description: String) -> Result<String> {
-        let timer = Timer::new(duration, description);
-        let timers_clone = self.timers.clone();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let timer = Timer::new(id, duration, description);
 
-        let (tx, mut rx) = channel(1);
-        timers_clone.lock().await.push(timer);
+        let mut timers = self.timers.lock().await;
+        let task = self.spawn_completion(id, duration);
+        timers.insert(
+            id,
+            TimerEntry {
+                timer,
+                task: Some(task),
+            },
+        );
+        Self::persist(self.storage.as_ref(), &timers).await;
+        drop(timers);
 
-        tokio::spawn(async move {
-            sleep(duration).await;
-            let mut timers = timers_clone.lock().await;
-            if let Some(pos) = timers.iter().position(|t| t.is_completed()) {
-                let result = Notification::new()
-                    .summary("Timer completed")
-                    .body(&timers[pos].description)
-                    .show();
+        Ok(format!("Timer set for {} seconds", duration.as_secs()))
+    }
 
-                timers.remove(pos);
+    async fn list(&self) -> Result<Vec<Timer>> {
+        let mut timers: Vec<Timer> = self
+            .timers
+            .lock()
+            .await
+            .values()
+            .map(|entry| entry.timer.clone())
+            .collect();
+        timers.sort_by_key(|timer| timer.id);
+        Ok(timers)
+    }
 
-                if let Err(e) = tx.send(result).await {
-                    error!("Failed to send result: {}", e);
-                }
+    async fn cancel(&self, id: u64) -> Result<Timer> {
+        let mut timers = self.timers.lock().await;
+        let entry = timers.remove(&id).ok_or(Error::TimerNotFound(id))?;
+        Self::persist(self.storage.as_ref(), &timers).await;
+        drop(timers);
+        if let Some(task) = entry.task {
+            task.abort();
+        }
+        Ok(entry.timer)
+    }
+
+    async fn pause(&self, id: u64) -> Result<Timer> {
+        let mut timers = self.timers.lock().await;
+        let entry = timers.get_mut(&id).ok_or(Error::TimerNotFound(id))?;
+        if !entry.timer.is_paused() {
+            if let Some(task) = entry.task.take() {
+                task.abort();
             }
-        });
+            entry.timer.state = TimerState::Paused {
+                remaining: entry.timer.remaining(),
+            };
+        }
+        let timer = entry.timer.clone();
+        Self::persist(self.storage.as_ref(), &timers).await;
+        drop(timers);
+        Ok(timer)
+    }
 
-        if let Some(result) = rx.recv().await {
-            result?;
+    async fn resume(&self, id: u64) -> Result<Timer> {
+        let mut timers = self.timers.lock().await;
+        let entry = timers.get_mut(&id).ok_or(Error::TimerNotFound(id))?;
+        if let TimerState::Paused { remaining } = entry.timer.state {
+            entry.timer.state = TimerState::Running {
+                ends_at: SystemTime::now() + remaining,
+            };
+            entry.task = Some(self.spawn_completion(id, remaining));
+        }
+        let timer = entry.timer.clone();
+        Self::persist(self.storage.as_ref(), &timers).await;
+        drop(timers);
+        Ok(timer)
+    }
+
+    async fn remaining(&self, id: u64) -> Result<Duration> {
+        self.timers
+            .lock()
+            .await
+            .get(&id)
+            .map(|entry| entry.timer.remaining())
+            .ok_or(Error::TimerNotFound(id))
+    }
+}
+
+impl Drop for MemoryTimer {
+    fn drop(&mut self) {
+        if let Ok(timers) = self.timers.try_lock() {
+            timers
+                .values()
+                .filter_map(|entry| entry.task.as_ref())
+                .for_each(JoinHandle::abort);
         }
-        Ok(format!("Timer set for {} seconds", duration.as_secs()))
     }
 }
 
@@ -60,13 +227,81 @@ mod tests {
     use super::*;
     use crate::error::Result;
     use std::time::Duration;
+    use tokio::time::timeout;
 
     #[tokio::test]
     async fn test_memory_timer() -> Result<()> {
-        let timer_service = MemoryTimer::new();
+        let (events, _) = broadcast::channel(1);
+        let timer_service = MemoryTimer::new(events);
         let description = "Test timer for 1 second".to_string();
         let result = timer_service.set(Duration::from_secs(1), description).await;
         assert!(result.is_ok());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_memory_timer_returns_before_completion() -> Result<()> {
+        let (events, mut receiver) = broadcast::channel(1);
+        let timer_service = MemoryTimer::new(events);
+
+        let result = timeout(
+            Duration::from_millis(100),
+            timer_service.set(Duration::from_secs(1), "pasta".to_string()),
+        )
+        .await;
+        assert!(result.is_ok(), "Setting a timer blocked until completion");
+
+        let event = timeout(Duration::from_secs(2), receiver.recv()).await;
+        match event {
+            Ok(Ok(Event::TimerCompleted { description })) => assert_eq!(description, "pasta"),
+            other => panic!("Expected a timer completion event, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_timer_pause_and_resume() -> Result<()> {
+        let (events, _) = broadcast::channel(1);
+        let timer_service = MemoryTimer::new(events);
+        timer_service
+            .set(Duration::from_secs(60), "pasta".to_string())
+            .await?;
+        let id = timer_service.list().await?[0].id;
+
+        let paused = timer_service.pause(id).await?;
+        assert!(paused.is_paused());
+        let remaining = timer_service.remaining(id).await?;
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(timer_service.remaining(id).await?, remaining);
+
+        let resumed = timer_service.resume(id).await?;
+        assert!(!resumed.is_paused());
+        assert!(resumed.remaining() <= remaining);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_timer_cancel() -> Result<()> {
+        let (events, mut receiver) = broadcast::channel(1);
+        let timer_service = MemoryTimer::new(events);
+        timer_service
+            .set(Duration::from_millis(100), "tea".to_string())
+            .await?;
+        let id = timer_service.list().await?[0].id;
+
+        let cancelled = timer_service.cancel(id).await?;
+        assert_eq!(cancelled.description, "tea");
+        assert!(timer_service.list().await?.is_empty());
+        assert!(matches!(
+            timer_service.cancel(id).await,
+            Err(Error::TimerNotFound(_))
+        ));
+
+        let event = timeout(Duration::from_millis(300), receiver.recv()).await;
+        assert!(event.is_err(), "Cancelled timer still completed");
+
+        Ok(())
+    }
 }
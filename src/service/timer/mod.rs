@@ -1,2 +1,6 @@
+pub mod alarm_service;
+pub mod file_alarm;
+pub mod file_timer;
+pub mod memory_alarm;
 pub mod memory_timer;
 pub mod timer_service;
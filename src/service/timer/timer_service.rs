@@ -1,8 +1,18 @@
 use crate::error::Result;
+use crate::model::timer::Timer;
 use async_trait::async_trait;
 use std::time::Duration;
 
 #[async_trait]
 pub trait TimerService: Send + Sync {
     async fn set(&self, duration: Duration, description: String) -> Result<String>;
+    async fn list(&self) -> Result<Vec<Timer>>;
+    async fn cancel(&self, id: u64) -> Result<Timer>;
+    async fn pause(&self, id: u64) -> Result<Timer>;
+    async fn resume(&self, id: u64) -> Result<Timer>;
+    async fn remaining(&self, id: u64) -> Result<Duration>;
+
+    async fn missed(&self) -> Result<Vec<Timer>> {
+        Ok(Vec::new())
+    }
 }
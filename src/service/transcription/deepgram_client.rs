@@ -1,11 +1,23 @@
 use super::transcription_service::TranscriptionService;
+use super::vocabulary::Vocabulary;
+use crate::audio::{to_f32, to_i16, TARGET_SAMPLE_RATE};
 use crate::error::{Error, Result};
+use crate::model::transcription::{Alternative, Hypothesis, Transcript, Word};
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::{BoxStream, StreamExt};
+use futures::SinkExt;
+use log::warn;
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{json, Value};
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
 use url::Url;
 
+const ALTERNATIVES: usize = 3;
+
 pub struct DeepgramClient {
     client: Client,
     api_key: String,
@@ -21,17 +33,116 @@ impl DeepgramClient {
             base_url: Url::parse(base_url)?,
         })
     }
+
+    fn add_keywords(url: &mut Url, vocabulary: &Vocabulary) {
+        let mut query_pairs = url.query_pairs_mut();
+        for term in vocabulary.terms() {
+            query_pairs.append_pair("keywords", term);
+        }
+    }
+
+    fn live_url(&self, vocabulary: &Vocabulary) -> Result<Url> {
+        let mut url = self.base_url.join("listen")?;
+        let scheme = if url.scheme() == "http" { "ws" } else { "wss" };
+        url.set_scheme(scheme)
+            .map_err(|()| Error::Transcription(format!("Invalid live URL: {url}")))?;
+        url.query_pairs_mut()
+            .append_pair("model", "nova-2")
+            .append_pair("smart_format", "true")
+            .append_pair("interim_results", "true")
+            .append_pair("encoding", "linear16")
+            .append_pair("sample_rate", &TARGET_SAMPLE_RATE.to_string())
+            .append_pair("channels", "1");
+        Self::add_keywords(&mut url, vocabulary);
+        Ok(url)
+    }
+
+    fn parse_word(word: &Value) -> Option<Word> {
+        let text = word
+            .get("punctuated_word")
+            .or_else(|| word.get("word"))
+            .and_then(Value::as_str)?;
+        Some(Word::new(
+            text,
+            to_f32(word.get("start").and_then(Value::as_f64)?),
+            to_f32(word.get("end").and_then(Value::as_f64)?),
+            word.get("confidence").and_then(Value::as_f64).map(to_f32),
+        ))
+    }
+
+    fn parse_channel(channel: &Value) -> Option<Transcript> {
+        let alternatives = channel.get("alternatives").and_then(Value::as_array)?;
+        let (best, rest) = alternatives.split_first()?;
+        let confidence = |alternative: &Value| {
+            alternative
+                .get("confidence")
+                .and_then(Value::as_f64)
+                .map(to_f32)
+        };
+
+        let text = best.get("transcript").and_then(Value::as_str)?;
+        let words = best
+            .get("words")
+            .and_then(Value::as_array)
+            .map(|words| words.iter().filter_map(Self::parse_word).collect())
+            .unwrap_or_default();
+        let alternatives = rest
+            .iter()
+            .filter_map(|alternative| {
+                let text = alternative.get("transcript").and_then(Value::as_str)?;
+                Some(Alternative::new(text, confidence(alternative)))
+            })
+            .collect();
+        let language = channel
+            .get("detected_language")
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        Some(
+            Transcript::new(text, language)
+                .with_confidence(confidence(best))
+                .with_words(words)
+                .with_alternatives(alternatives),
+        )
+    }
+
+    fn parse_live_result(text: &str) -> Result<Option<Hypothesis>> {
+        let json: Value = serde_json::from_str(text)?;
+        if json.get("type").and_then(Value::as_str) != Some("Results") {
+            return Ok(None);
+        }
+        let Some(transcript) = json.get("channel").and_then(Self::parse_channel) else {
+            return Ok(None);
+        };
+        if transcript.text.is_empty() {
+            return Ok(None);
+        }
+        let is_final = json
+            .get("is_final")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        Ok(Some(Hypothesis::new(transcript, is_final)))
+    }
+
+    fn encode_frame(samples: &[f32]) -> Bytes {
+        samples
+            .iter()
+            .flat_map(|&sample| to_i16(sample).to_le_bytes())
+            .collect()
+    }
 }
 
 #[async_trait]
 impl TranscriptionService for DeepgramClient {
-    async fn transcribe(&self, audio: &Bytes) -> Result<String> {
+    async fn transcribe(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Transcript> {
         let mut url = self.base_url.join("listen")?;
         {
             let mut query_pairs = url.query_pairs_mut();
             query_pairs.append_pair("model", "nova-2");
             query_pairs.append_pair("smart_format", "true");
+            query_pairs.append_pair("alternatives", &ALTERNATIVES.to_string());
         }
+        Self::add_keywords(&mut url, vocabulary);
 
         let response = self
             .client
@@ -44,20 +155,9 @@ impl TranscriptionService for DeepgramClient {
 
         if response.status().is_success() {
             let json: Value = response.json().await?;
-            let transcript = json
-                .get("results")
-                .and_then(|r| r.get("channels"))
-                .and_then(|channels| channels.get(0))
-                .and_then(|channel| channel.get("alternatives"))
-                .and_then(|alternatives| alternatives.get(0))
-                .and_then(|alternative| alternative.get("transcript"))
-                .and_then(|t| t.as_str())
-                .map(|s| s.to_string());
-
-            match transcript {
-                Some(text) => Ok(text),
-                None => Err(Error::ApiError("No transcription result found".to_string())),
-            }
+            json.pointer("/results/channels/0")
+                .and_then(Self::parse_channel)
+                .ok_or(Error::ApiError("No transcription result found".to_string()))
         } else {
             let error_json: Value = response.json().await?;
             Err(Error::ApiError(
@@ -68,4 +168,133 @@ impl TranscriptionService for DeepgramClient {
             ))
         }
     }
+
+    async fn transcribe_stream(
+        &self,
+        mut audio: BoxStream<'static, Vec<f32>>,
+        vocabulary: &Vocabulary,
+    ) -> Result<BoxStream<'static, Result<Hypothesis>>> {
+        let mut request = self.live_url(vocabulary)?.as_str().into_client_request()?;
+        request.headers_mut().insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Token {}", self.api_key))?,
+        );
+        let (ws_stream, _) = connect_async(request).await?;
+        let (mut ws_tx, ws_rx) = ws_stream.split();
+
+        tokio::spawn(async move {
+            while let Some(chunk) = audio.next().await {
+                if let Err(e) = ws_tx
+                    .send(Message::Binary(Self::encode_frame(&chunk)))
+                    .await
+                {
+                    warn!("Failed to stream audio to Deepgram: {}", e);
+                    return;
+                }
+            }
+            let close = json!({ "type": "CloseStream" }).to_string();
+            let _ = ws_tx.send(Message::Text(close.into())).await;
+        });
+
+        let hypotheses = ws_rx
+            .take_while(|message| futures::future::ready(!matches!(message, Ok(Message::Close(_)))))
+            .filter_map(|message| async move {
+                match message {
+                    Ok(Message::Text(text)) => Self::parse_live_result(&text).transpose(),
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e.into())),
+                }
+            });
+        Ok(hypotheses.boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_live_result() -> Result<()> {
+        let interim = r#"{"type": "Results", "is_final": false, "channel": {"alternatives": [{"transcript": "what is the", "confidence": 0.75}]}}"#;
+        assert_eq!(
+            DeepgramClient::parse_live_result(interim)?,
+            Some(Hypothesis::new(
+                Transcript::new("what is the", None).with_confidence(Some(0.75)),
+                false
+            ))
+        );
+
+        let last = r#"{"type": "Results", "is_final": true, "channel": {"alternatives": [{"transcript": "What is the weather?"}]}}"#;
+        assert_eq!(
+            DeepgramClient::parse_live_result(last)?,
+            Some(Hypothesis::new(
+                Transcript::new("What is the weather?", None),
+                true
+            ))
+        );
+
+        let silence = r#"{"type": "Results", "is_final": true, "channel": {"alternatives": [{"transcript": ""}]}}"#;
+        assert_eq!(DeepgramClient::parse_live_result(silence)?, None);
+        assert_eq!(
+            DeepgramClient::parse_live_result(r#"{"type": "Metadata"}"#)?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_channel() -> Result<()> {
+        let channel = json!({
+            "alternatives": [
+                {
+                    "transcript": "close the window",
+                    "confidence": 0.5,
+                    "words": [
+                        { "word": "close", "start": 0.25, "end": 0.5, "confidence": 0.5, "punctuated_word": "Close" },
+                        { "word": "the", "start": 0.5, "end": 0.75, "confidence": 0.75 },
+                        { "word": "window", "start": 0.75, "end": 1.25 }
+                    ]
+                },
+                { "transcript": "clothes the window", "confidence": 0.25 }
+            ]
+        });
+
+        let transcript = DeepgramClient::parse_channel(&channel)
+            .ok_or(Error::ApiError("No transcript".to_string()))?;
+        assert_eq!(transcript.text, "close the window");
+        assert_eq!(transcript.confidence, Some(0.5));
+        assert_eq!(
+            transcript.words,
+            vec![
+                Word::new("Close", 0.25, 0.5, Some(0.5)),
+                Word::new("the", 0.5, 0.75, Some(0.75)),
+                Word::new("window", 0.75, 1.25, None),
+            ]
+        );
+        assert_eq!(
+            transcript.alternatives,
+            vec![Alternative::new("clothes the window", Some(0.25))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_live_url_adds_keywords() -> Result<()> {
+        let client = DeepgramClient {
+            client: Client::new(),
+            api_key: String::new(),
+            base_url: Url::parse("https://api.deepgram.com/v1/")?,
+        };
+        let vocabulary = Vocabulary::new(["ferris", "St. Pölten"].map(String::from), 0.8);
+        let url = client.live_url(&vocabulary)?;
+
+        assert_eq!(url.scheme(), "wss");
+        let keywords: Vec<String> = url
+            .query_pairs()
+            .filter(|(key, _)| key == "keywords")
+            .map(|(_, value)| value.into_owned())
+            .collect();
+        assert_eq!(keywords, ["ferris", "St. Pölten"]);
+        Ok(())
+    }
 }
@@ -1,53 +1,127 @@
+/*
+ * Transcribes with a local Whisper model.
+ * Streaming decodes a window that grows with the audio, about once per second of new audio, as a partial result.
+ * Once the window reaches `WINDOW_LENGTH` its text is final and a new window starts.
+ * A language of "auto" lets Whisper detect it, the language it decoded in is returned with the text.
+ * The vocabulary is appended to the initial prompt, which biases Whisper towards those spellings.
+ * Words are assembled from tokens, a token starting with a space starts a new word, and confidence is the mean token probability.
+ */
 use super::transcription_service::TranscriptionService;
+use super::vocabulary::Vocabulary;
+use crate::audio::{count_to_f32, normalize_wav, to_f32, TARGET_SAMPLE_RATE};
 use crate::error::{Error, Result};
+use crate::model::transcription::{Hypothesis, Transcript, Word};
 use async_trait::async_trait;
 use bytes::Bytes;
-use hound::WavReader;
-use std::{io::Cursor, sync::Arc};
-use whisper_rs::{FullParams, WhisperContext, WhisperContextParameters};
+use futures::channel::mpsc;
+use futures::stream::{BoxStream, StreamExt};
+use std::sync::Arc;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+    WhisperToken,
+};
+
+const PARTIAL_INTERVAL: usize = TARGET_SAMPLE_RATE as usize;
+const WINDOW_LENGTH: usize = TARGET_SAMPLE_RATE as usize * 20;
+
+#[derive(Clone, Debug)]
+pub struct DecodingOptions {
+    pub language: String,
+    pub translate: bool,
+    pub beam_size: usize,
+    pub threads: usize,
+    pub temperature_increment: f32,
+    pub initial_prompt: String,
+}
+
+struct PendingWord {
+    bytes: Vec<u8>,
+    start: i64,
+    end: i64,
+    probabilities: Vec<f32>,
+}
+
+impl PendingWord {
+    fn finish(self) -> Option<Word> {
+        let text = String::from_utf8_lossy(&self.bytes).trim().to_string();
+        (!text.is_empty()).then(|| {
+            Word::new(
+                text,
+                centiseconds_to_secs(self.start),
+                centiseconds_to_secs(self.end),
+                mean(&self.probabilities),
+            )
+        })
+    }
+}
+
+fn centiseconds_to_secs(centiseconds: i64) -> f32 {
+    to_f32(i32::try_from(centiseconds).unwrap_or(i32::MAX)) / 100.0
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / count_to_f32(values.len()))
+}
 
 pub struct LocalWhisperClient {
     pub context: Arc<WhisperContext>,
+    options: Arc<DecodingOptions>,
 }
 
 impl LocalWhisperClient {
-    pub fn new(model: impl Into<String>, use_gpu: bool) -> Result<Self> {
+    pub fn new(model: impl Into<String>, use_gpu: bool, options: DecodingOptions) -> Result<Self> {
         let mut params = WhisperContextParameters::default();
         params.use_gpu = use_gpu;
         let context = Arc::new(WhisperContext::new_with_params(&model.into(), params)?);
-        Ok(Self { context })
+        Ok(Self {
+            context,
+            options: Arc::new(options),
+        })
     }
-}
-
-#[async_trait]
-impl TranscriptionService for LocalWhisperClient {
-    async fn transcribe(&self, audio: &Bytes) -> Result<String> {
-        let cursor = Cursor::new(audio);
-        let mut reader = WavReader::new(cursor)?;
-
-        let spec = reader.spec();
-        if spec.channels != 1 || spec.sample_rate != 16000 {
-            return Err(Error::AudioCodec(
-                "WAV file must be mono and have a sample rate of 16000 Hz".to_string(),
-            ));
-        }
 
-        let samples: Vec<f32> = reader
-            .samples::<f32>()
-            .map(|s| s.map_err(|e| Error::AudioProcessing(e)))
-            .collect::<Result<_>>()?;
+    fn params<'a>(options: &'a DecodingOptions, vocabulary: &Vocabulary) -> FullParams<'a, 'a> {
+        let strategy = if options.beam_size > 1 {
+            SamplingStrategy::BeamSearch {
+                beam_size: i32::try_from(options.beam_size).unwrap_or(i32::MAX),
+                patience: -1.0,
+            }
+        } else {
+            SamplingStrategy::Greedy { best_of: 5 }
+        };
+        let mut params = FullParams::new(strategy);
 
-        let mut params = FullParams::new(whisper_rs::SamplingStrategy::Greedy { best_of: 5 });
+        params.set_translate(options.translate);
+        params.set_language(Some(&options.language));
+        params.set_temperature_inc(options.temperature_increment);
+        params.set_token_timestamps(true);
+        if options.threads > 0 {
+            params.set_n_threads(i32::try_from(options.threads).unwrap_or(i32::MAX));
+        }
+        let prompt = [options.initial_prompt.clone(), vocabulary.prompt()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !prompt.is_empty() {
+            params.set_initial_prompt(&prompt);
+        }
+        params
+    }
 
-        params.set_translate(false);
-        params.set_language(Some("en"));
+    fn decode(
+        context: &WhisperContext,
+        options: &DecodingOptions,
+        vocabulary: &Vocabulary,
+        samples: &[f32],
+    ) -> Result<Transcript> {
+        let mut params = Self::params(options, vocabulary);
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
 
-        let mut state = self.context.create_state()?;
-        state.full(params, &samples)?;
+        let mut state = context.create_state()?;
+        state.full(params, samples)?;
 
         let num_segments = state.full_n_segments()?;
         let mut text = String::new();
@@ -56,7 +130,125 @@ impl TranscriptionService for LocalWhisperClient {
             text.push_str(&state.full_get_segment_text(i)?);
             text.push(' ');
         }
+        let language = whisper_rs::get_lang_str(state.full_lang_id_from_state()?).map(String::from);
+        let (words, confidence) = Self::words(&state, context.token_eot())?;
+
+        Ok(Transcript::new(text.trim(), language)
+            .with_confidence(confidence)
+            .with_words(words))
+    }
+
+    fn words(state: &WhisperState, eot: WhisperToken) -> Result<(Vec<Word>, Option<f32>)> {
+        let mut words = Vec::new();
+        let mut probabilities = Vec::new();
+        let mut pending: Option<PendingWord> = None;
+
+        for segment in 0..state.full_n_segments()? {
+            for token in 0..state.full_n_tokens(segment)? {
+                if state.full_get_token_id(segment, token)? >= eot {
+                    continue;
+                }
+                let data = state.full_get_token_data(segment, token)?;
+                let bytes = state.full_get_token_bytes(segment, token)?;
+                probabilities.push(data.p);
+
+                match &mut pending {
+                    Some(word) if !bytes.starts_with(b" ") => {
+                        word.bytes.extend(bytes);
+                        word.end = data.t1;
+                        word.probabilities.push(data.p);
+                    }
+                    _ => {
+                        words.extend(pending.take().and_then(PendingWord::finish));
+                        pending = Some(PendingWord {
+                            bytes,
+                            start: data.t0,
+                            end: data.t1,
+                            probabilities: vec![data.p],
+                        });
+                    }
+                }
+            }
+        }
+        words.extend(pending.and_then(PendingWord::finish));
+
+        Ok((words, mean(&probabilities)))
+    }
+
+    async fn decode_window(
+        context: Arc<WhisperContext>,
+        options: Arc<DecodingOptions>,
+        vocabulary: Arc<Vocabulary>,
+        samples: Vec<f32>,
+    ) -> Result<Transcript> {
+        tokio::task::spawn_blocking(move || Self::decode(&context, &options, &vocabulary, &samples))
+            .await
+            .map_err(|e| Error::Transcription(e.to_string()))?
+    }
+}
+
+#[async_trait]
+impl TranscriptionService for LocalWhisperClient {
+    async fn transcribe(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Transcript> {
+        let samples = normalize_wav(audio)?;
+        Self::decode(&self.context, &self.options, vocabulary, &samples)
+    }
+
+    async fn transcribe_stream(
+        &self,
+        mut audio: BoxStream<'static, Vec<f32>>,
+        vocabulary: &Vocabulary,
+    ) -> Result<BoxStream<'static, Result<Hypothesis>>> {
+        let context = self.context.clone();
+        let options = self.options.clone();
+        let vocabulary = Arc::new(vocabulary.clone());
+        let (tx, rx) = mpsc::unbounded();
+
+        tokio::spawn(async move {
+            let mut window = Vec::new();
+            let mut window_start = 0;
+            let mut undecoded = 0;
+            while let Some(chunk) = audio.next().await {
+                undecoded += chunk.len();
+                window.extend(chunk);
+                if undecoded < PARTIAL_INTERVAL {
+                    continue;
+                }
+                undecoded = 0;
+
+                let is_final = window.len() >= WINDOW_LENGTH;
+                let samples_length = window.len();
+                let offset = count_to_f32(window_start) / to_f32(TARGET_SAMPLE_RATE);
+                let samples = if is_final {
+                    std::mem::take(&mut window)
+                } else {
+                    window.clone()
+                };
+                let hypothesis = Self::decode_window(
+                    context.clone(),
+                    options.clone(),
+                    vocabulary.clone(),
+                    samples,
+                )
+                .await
+                .map(|transcript| Hypothesis::new(transcript.offset(offset), is_final));
+                if is_final {
+                    window_start += samples_length;
+                }
+                if tx.unbounded_send(hypothesis).is_err() {
+                    return;
+                }
+            }
+
+            if !window.is_empty() {
+                let offset = count_to_f32(window_start) / to_f32(TARGET_SAMPLE_RATE);
+                let hypothesis = Self::decode_window(context, options, vocabulary, window)
+                    .await
+                    .map(|transcript| Hypothesis::new(transcript.offset(offset), true));
+                let _ = tx.unbounded_send(hypothesis);
+            }
+        });
 
-        Ok(text.trim().to_string())
+        Ok(rx.boxed())
     }
 }
@@ -1,7 +1,11 @@
 pub mod deepgram_client;
 pub mod local_whisper_client;
+pub mod open_ai_compatible_client;
 pub mod transcription_service;
+pub mod vocabulary;
 
 pub use deepgram_client::DeepgramClient;
-pub use local_whisper_client::LocalWhisperClient;
+pub use local_whisper_client::{DecodingOptions, LocalWhisperClient};
+pub use open_ai_compatible_client::OpenAiCompatibleClient;
 pub use transcription_service::TranscriptionService;
+pub use vocabulary::Vocabulary;
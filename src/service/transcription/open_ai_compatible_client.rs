@@ -0,0 +1,311 @@
+/*
+ * Transcribes with any server implementing OpenAI's `audio/transcriptions` endpoint, such as faster-whisper servers.
+ * The server cannot take audio while it is recorded, so streaming collects the audio and sends one final result.
+ */
+use super::transcription_service::TranscriptionService;
+use super::vocabulary::Vocabulary;
+use crate::audio::{count_to_f32, encode_wav, to_f32};
+use crate::error::{Error, Result};
+use crate::model::transcription::{Hypothesis, Transcript, Word};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt};
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+pub struct OpenAiCompatibleClient {
+    client: Client,
+    base_url: Url,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatibleClient {
+    pub fn new(base_url: &str, model: impl Into<String>, api_key: Option<String>) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().build()?,
+            base_url: Url::parse(base_url)?,
+            model: model.into(),
+            api_key,
+        })
+    }
+
+    fn form(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Form> {
+        let file = Part::bytes(audio.to_vec())
+            .file_name("recording.wav")
+            .mime_str("audio/wav")?;
+        let mut form = Form::new()
+            .part("file", file)
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "word");
+        if !vocabulary.terms().is_empty() {
+            form = form.text("prompt", vocabulary.prompt());
+        }
+        Ok(form)
+    }
+
+    fn parse_response(json: &Value) -> Result<Transcript> {
+        let text = json
+            .get("text")
+            .and_then(Value::as_str)
+            .ok_or(Error::ApiError("No transcription result found".to_string()))?;
+        let language = json
+            .get("language")
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        let words = json
+            .get("words")
+            .and_then(Value::as_array)
+            .map(|words| {
+                words
+                    .iter()
+                    .filter_map(|word| {
+                        Some(Word::new(
+                            word.get("word").and_then(Value::as_str)?.trim(),
+                            to_f32(word.get("start").and_then(Value::as_f64)?),
+                            to_f32(word.get("end").and_then(Value::as_f64)?),
+                            None,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let log_probabilities: Vec<f64> = json
+            .get("segments")
+            .and_then(Value::as_array)
+            .map(|segments| {
+                segments
+                    .iter()
+                    .filter_map(|segment| segment.get("avg_logprob").and_then(Value::as_f64))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let confidence = (!log_probabilities.is_empty()).then(|| {
+            let mean = log_probabilities.iter().sum::<f64>()
+                / f64::from(count_to_f32(log_probabilities.len()));
+            to_f32(mean.exp())
+        });
+
+        Ok(Transcript::new(text.trim(), language)
+            .with_confidence(confidence)
+            .with_words(words))
+    }
+}
+
+#[async_trait]
+impl TranscriptionService for OpenAiCompatibleClient {
+    async fn transcribe(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Transcript> {
+        let url = self.base_url.join("audio/transcriptions")?;
+        let mut request = self
+            .client
+            .post(url)
+            .multipart(self.form(audio, vocabulary)?);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let json: Value = response.json().await?;
+            return Self::parse_response(&json);
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body).ok().and_then(|json| {
+            json.pointer("/error/message")
+                .or_else(|| json.get("detail"))
+                .and_then(Value::as_str)
+                .map(String::from)
+        });
+        Err(Error::ApiError(match message {
+            Some(message) => format!("{status}: {message}"),
+            None => status.to_string(),
+        }))
+    }
+
+    async fn transcribe_stream(
+        &self,
+        audio: BoxStream<'static, Vec<f32>>,
+        vocabulary: &Vocabulary,
+    ) -> Result<BoxStream<'static, Result<Hypothesis>>> {
+        let samples: Vec<f32> = audio.concat().await;
+        let transcript = self.transcribe(&encode_wav(&samples)?, vocabulary).await;
+        Ok(stream::once(
+            async move { transcript.map(|transcript| Hypothesis::new(transcript, true)) },
+        )
+        .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    async fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> Result<(String, JoinHandle<String>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let base_url = format!("http://{}/v1/", listener.local_addr()?);
+
+        let server = tokio::spawn(async move {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return String::new();
+            };
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while let Ok(read) = socket.read(&mut buffer).await {
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+
+                let text = String::from_utf8_lossy(&request);
+                let Some(header_end) = text.find("\r\n\r\n") else {
+                    continue;
+                };
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())
+                            .flatten()
+                    })
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        Ok((base_url, server))
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_uploads_wav() -> Result<()> {
+        let (base_url, server) = serve_once(
+            "200 OK",
+            r#"{
+                "text": " Close the window.",
+                "language": "en",
+                "segments": [{ "avg_logprob": 0.0 }],
+                "words": [
+                    { "word": " Close", "start": 0.0, "end": 0.5 },
+                    { "word": " the", "start": 0.5, "end": 0.75 },
+                    { "word": " window.", "start": 0.75, "end": 1.25 }
+                ]
+            }"#,
+        )
+        .await?;
+        let client = OpenAiCompatibleClient::new(&base_url, "small", Some("secret".to_string()))?;
+        let vocabulary = Vocabulary::new(["ferris".to_string()], 0.8);
+
+        let audio = encode_wav(&[0.0; 1600])?;
+        let transcript = client.transcribe(&audio, &vocabulary).await?;
+        assert_eq!(transcript.text, "Close the window.");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.confidence, Some(1.0));
+        assert_eq!(transcript.words[2], Word::new("window.", 0.75, 1.25, None));
+
+        let request = server.await.map_err(|e| Error::ApiError(e.to_string()))?;
+        assert!(request.starts_with("POST /v1/audio/transcriptions HTTP/1.1"));
+        assert!(request
+            .to_lowercase()
+            .contains("authorization: bearer secret"));
+        assert!(request.contains("name=\"model\"\r\n\r\nsmall"));
+        assert!(request.contains("name=\"prompt\"\r\n\r\nferris"));
+        assert!(request.contains("filename=\"recording.wav\""));
+        assert!(request.contains("Content-Type: audio/wav"));
+        assert!(request.contains("RIFF"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_without_api_key() -> Result<()> {
+        let (base_url, server) = serve_once("200 OK", r#"{ "text": "Hello" }"#).await?;
+        let client = OpenAiCompatibleClient::new(&base_url, "small", None)?;
+
+        let transcript = client
+            .transcribe(&encode_wav(&[0.0; 160])?, &Vocabulary::default())
+            .await?;
+        assert_eq!(transcript, Transcript::new("Hello", None));
+
+        let request = server.await.map_err(|e| Error::ApiError(e.to_string()))?;
+        assert!(!request.to_lowercase().contains("authorization:"));
+        assert!(!request.contains("name=\"prompt\""));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_reports_server_errors() -> Result<()> {
+        let (base_url, _server) = serve_once(
+            "401 Unauthorized",
+            r#"{ "error": { "message": "Invalid API key" } }"#,
+        )
+        .await?;
+        let client = OpenAiCompatibleClient::new(&base_url, "small", Some("wrong".to_string()))?;
+
+        let result = client
+            .transcribe(&encode_wav(&[0.0; 160])?, &Vocabulary::default())
+            .await;
+        assert!(
+            matches!(result, Err(Error::ApiError(message)) if message == "401 Unauthorized: Invalid API key")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_reports_status_for_non_json_errors() -> Result<()> {
+        let (base_url, _server) =
+            serve_once("502 Bad Gateway", "<html><body>Bad Gateway</body></html>").await?;
+        let client = OpenAiCompatibleClient::new(&base_url, "small", None)?;
+
+        let result = client
+            .transcribe(&encode_wav(&[0.0; 160])?, &Vocabulary::default())
+            .await;
+        assert!(matches!(result, Err(Error::ApiError(message)) if message == "502 Bad Gateway"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_stream_sends_one_final_result() -> Result<()> {
+        let (base_url, _server) = serve_once("200 OK", r#"{ "text": "Close the window" }"#).await?;
+        let client = OpenAiCompatibleClient::new(&base_url, "small", None)?;
+
+        let audio = stream::iter(vec![vec![0.0; 800], vec![0.0; 800]]).boxed();
+        let hypotheses: Vec<Hypothesis> = client
+            .transcribe_stream(audio, &Vocabulary::default())
+            .await?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_>>()?;
+        assert_eq!(
+            hypotheses,
+            vec![Hypothesis::new(
+                Transcript::new("Close the window", None),
+                true
+            )]
+        );
+        Ok(())
+    }
+}
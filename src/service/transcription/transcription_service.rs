@@ -1,8 +1,16 @@
+use super::vocabulary::Vocabulary;
 use crate::error::Result;
+use crate::model::transcription::{Hypothesis, Transcript};
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::stream::BoxStream;
 
 #[async_trait]
 pub trait TranscriptionService: Send + Sync {
-    async fn transcribe(&self, audio: &Bytes) -> Result<String>;
+    async fn transcribe(&self, audio: &Bytes, vocabulary: &Vocabulary) -> Result<Transcript>;
+    async fn transcribe_stream(
+        &self,
+        audio: BoxStream<'static, Vec<f32>>,
+        vocabulary: &Vocabulary,
+    ) -> Result<BoxStream<'static, Result<Hypothesis>>>;
 }
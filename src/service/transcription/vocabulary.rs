@@ -0,0 +1,170 @@
+/*
+ * Names the transcriber should expect, such as wake words, locations and timer labels.
+ * Transcribers are biased towards them, and `correct` replaces words in a transcript
+ * that are spelled close enough to one of them.
+ */
+use crate::audio::count_to_f32;
+
+#[derive(Clone, Debug, Default)]
+pub struct Vocabulary {
+    terms: Vec<String>,
+    threshold: f32,
+}
+
+impl Vocabulary {
+    pub fn new(terms: impl IntoIterator<Item = String>, threshold: f32) -> Self {
+        let mut vocabulary = Self {
+            terms: Vec::new(),
+            threshold,
+        };
+        vocabulary.extend(terms);
+        vocabulary
+    }
+
+    pub fn extend(&mut self, terms: impl IntoIterator<Item = String>) {
+        for term in terms {
+            let term = term.trim();
+            if term.is_empty()
+                || self
+                    .terms
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(term))
+            {
+                continue;
+            }
+            self.terms.push(term.to_string());
+        }
+    }
+
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    pub fn prompt(&self) -> String {
+        self.terms.join(", ")
+    }
+
+    pub fn correct(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut corrected = Vec::with_capacity(words.len());
+        let mut i = 0;
+        while i < words.len() {
+            match self.best_match(&words[i..]) {
+                Some((_, length, similarity)) if similarity >= 1.0 => {
+                    corrected.extend(words[i..i + length].iter().map(ToString::to_string));
+                    i += length;
+                }
+                Some((term, length, _)) => {
+                    let prefix: String = words[i]
+                        .chars()
+                        .take_while(|c| !c.is_alphanumeric())
+                        .collect();
+                    let last = words[i + length - 1];
+                    let suffix =
+                        &last[last.trim_end_matches(|c: char| !c.is_alphanumeric()).len()..];
+                    corrected.push(format!("{prefix}{term}{suffix}"));
+                    i += length;
+                }
+                None => {
+                    corrected.push(words[i].to_string());
+                    i += 1;
+                }
+            }
+        }
+        corrected.join(" ")
+    }
+
+    fn best_match(&self, words: &[&str]) -> Option<(&str, usize, f32)> {
+        self.terms
+            .iter()
+            .filter_map(|term| {
+                let length = term.split_whitespace().count();
+                let candidate = normalize(&words.get(..length)?.join(" "));
+                let similarity = similarity(&candidate, &normalize(term));
+                (similarity > self.threshold).then_some((term.as_str(), length, similarity))
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2).then(a.1.cmp(&b.1)))
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn similarity(a: &str, b: &str) -> f32 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - count_to_f32(levenshtein(a, b)) / count_to_f32(longest)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> Vocabulary {
+        Vocabulary::new(["ferris", "St. Pölten", "pasta"].map(String::from), 0.8)
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("ferris", "ferries"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_correct_replaces_close_words() {
+        let vocabulary = vocabulary();
+        assert_eq!(
+            vocabulary.correct("Ferries, what's the weather in Saint Pölten?"),
+            "ferris, what's the weather in Saint Pölten?"
+        );
+        assert_eq!(
+            vocabulary.correct("What's the weather in St Polten?"),
+            "What's the weather in St. Pölten?"
+        );
+        assert_eq!(
+            vocabulary.correct("Ferris, set a Pasta timer"),
+            "Ferris, set a Pasta timer"
+        );
+    }
+
+    #[test]
+    fn test_correct_keeps_unrelated_words() {
+        let vocabulary = vocabulary();
+        assert_eq!(
+            vocabulary.correct("In the past, Paris was far"),
+            "In the past, Paris was far"
+        );
+    }
+
+    #[test]
+    fn test_extend_skips_duplicates() {
+        let mut vocabulary = vocabulary();
+        vocabulary.extend(["Ferris", " ", "Berlin"].map(String::from));
+        assert_eq!(
+            vocabulary.terms(),
+            ["ferris", "St. Pölten", "pasta", "Berlin"]
+        );
+        assert_eq!(vocabulary.prompt(), "ferris, St. Pölten, pasta, Berlin");
+    }
+}
@@ -1,11 +1,59 @@
 use super::workspace_service::WorkspaceService;
 use crate::error::{Error, Result};
 use async_trait::async_trait;
+use directories::BaseDirs;
+use std::path::PathBuf;
 use std::process::Command;
+use tokio::fs;
+
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
 
 pub struct KWinClient;
 
 impl KWinClient {
+    fn entries<'a>(contents: &'a str, group: &str) -> Vec<(&'a str, &'a str)> {
+        let mut in_group = false;
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| {
+                if line.starts_with('[') {
+                    in_group = line.trim_matches(['[', ']']) == group;
+                    return false;
+                }
+                in_group
+            })
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect()
+    }
+
+    fn desktop_names(kwinrc: &str) -> Vec<String> {
+        Self::entries(kwinrc, "Desktops")
+            .into_iter()
+            .filter(|(key, value)| key.starts_with("Name_") && !value.is_empty())
+            .map(|(_, value)| value.to_string())
+            .collect()
+    }
+
+    fn application_name(desktop_entry: &str) -> Option<String> {
+        let entries = Self::entries(desktop_entry, "Desktop Entry");
+        let is_set = |wanted: &str, expected: &str| {
+            entries
+                .iter()
+                .any(|(key, value)| *key == wanted && *value == expected)
+        };
+        if !is_set("Type", "Application") || is_set("NoDisplay", "true") || is_set("Hidden", "true")
+        {
+            return None;
+        }
+
+        entries
+            .iter()
+            .find(|(key, _)| *key == "Name")
+            .map(|(_, value)| value.to_string())
+    }
+
     async fn qdbus(&self, command: &str, error_message: &str) -> Result<()> {
         let output = Command::new("qdbus")
             .args(command.split(" "))
@@ -25,11 +73,7 @@ impl KWinClient {
 
     async fn invoke_shortcut(&self, shortcut: &str, error_message: &str) -> Result<()> {
         self.qdbus(
-            format!(
-                "org.kde.kglobalaccel /component/kwin invokeShortcut {}",
-                shortcut
-            )
-            .as_str(),
+            format!("org.kde.kglobalaccel /component/kwin invokeShortcut {shortcut}").as_str(),
             error_message,
         )
         .await
@@ -56,6 +100,50 @@ impl WorkspaceService for KWinClient {
         self.invoke_shortcut("Show Desktop", "show desktop").await
     }
 
+    async fn workspace_names(&self) -> Result<Vec<String>> {
+        let Some(dirs) = BaseDirs::new() else {
+            return Ok(Vec::new());
+        };
+
+        match fs::read_to_string(dirs.config_dir().join("kwinrc")).await {
+            Ok(kwinrc) => Ok(Self::desktop_names(&kwinrc)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn app_names(&self) -> Result<Vec<String>> {
+        let mut data_dirs: Vec<PathBuf> = BaseDirs::new()
+            .map(|dirs| dirs.data_dir().to_path_buf())
+            .into_iter()
+            .collect();
+        let system_dirs =
+            std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| DEFAULT_DATA_DIRS.to_string());
+        data_dirs.extend(std::env::split_paths(&system_dirs));
+
+        let mut names = Vec::new();
+        for data_dir in data_dirs {
+            let Ok(mut entries) = fs::read_dir(data_dir.join("applications")).await else {
+                continue;
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path
+                    .extension()
+                    .is_none_or(|extension| extension != "desktop")
+                {
+                    continue;
+                }
+                if let Ok(contents) = fs::read_to_string(&path).await {
+                    names.extend(Self::application_name(&contents));
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
     async fn switch_workspace(&self, workspace: usize) -> Result<()> {
         self.qdbus(
             format!(
@@ -74,6 +162,27 @@ mod tests {
     use super::*;
     use crate::error::Result;
 
+    #[test]
+    fn test_desktop_names() {
+        let kwinrc = "[Desktops]\nId_1=abc\nName_1=Code\nName_2=Chat\nNumber=2\n\n[Windows]\nName_3=Ignored\n";
+
+        assert_eq!(KWinClient::desktop_names(kwinrc), vec!["Code", "Chat"]);
+    }
+
+    #[test]
+    fn test_application_name() {
+        let firefox = "[Desktop Entry]\nType=Application\nName=Firefox\nName[de]=Firefox-Browser\n\n[Desktop Action new-window]\nName=New Window\n";
+        let hidden = "[Desktop Entry]\nType=Application\nName=Helper\nNoDisplay=true\n";
+        let link = "[Desktop Entry]\nType=Link\nName=Website\n";
+
+        assert_eq!(
+            KWinClient::application_name(firefox).as_deref(),
+            Some("Firefox")
+        );
+        assert_eq!(KWinClient::application_name(hidden), None);
+        assert_eq!(KWinClient::application_name(link), None);
+    }
+
     #[tokio::test]
     async fn test_close_window() -> Result<()> {
         let kwin_client = KWinClient;
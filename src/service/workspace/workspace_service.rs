@@ -8,4 +8,12 @@ pub trait WorkspaceService: Send + Sync {
     async fn maximize_window(&self) -> Result<()>;
     async fn show_desktop(&self) -> Result<()>;
     async fn switch_workspace(&self, workspace: usize) -> Result<()>;
+
+    async fn workspace_names(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn app_names(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 }